@@ -14,7 +14,7 @@ public class ProviderUserSeederTests
     [Fact]
     public void CreateConfirmedAdmin_ProducesConfirmedProviderAdminWithWrappedKey()
     {
-        var providerKey = RustSdkService.GenerateOrganizationKeys().Key;
+        var providerKey = RustSdkService.GenerateProviderKeys(poolIndex: 1).Key;
         var ownerKeys = RustSdkService.GenerateUserKeys("owner@provider.test", "asdfasdfasdf");
         var owner = new User { Id = CombGuid.Generate(), PublicKey = ownerKeys.PublicKey };
         var provider = ProviderSeeder.Create(
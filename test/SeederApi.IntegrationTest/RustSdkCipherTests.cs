@@ -4,6 +4,7 @@ using Bit.Core.Vault.Enums;
 using Bit.Core.Vault.Models.Data;
 using Bit.RustSDK;
 using Bit.Seeder.Attributes;
+using Bit.Seeder.Enums;
 using Bit.Seeder.Factories;
 using Bit.Seeder.Models;
 using Xunit;
@@ -28,6 +29,19 @@ public sealed class RustSdkCipherTests
         Assert.Equal("SuperSecretP@ssw0rd!", RustSdkService.DecryptString(encrypted, orgKeys.Key));
     }
 
+    [Fact]
+    public void EncryptString_WithRegisteredKey_DecryptsWithSameKey()
+    {
+        var orgKeys = RustSdkService.GenerateOrganizationKeys();
+
+        var handle = RustSdkService.RegisterKey(orgKeys.Key);
+        var encrypted = RustSdkService.EncryptString("SuperSecretP@ssw0rd!", handle);
+        handle.Dispose();
+
+        Assert.Equal("SuperSecretP@ssw0rd!", RustSdkService.DecryptString(encrypted, orgKeys.Key));
+        Assert.Throws<ObjectDisposedException>(() => RustSdkService.EncryptString("after release", handle));
+    }
+
     [Fact]
     public void EncryptFields_DecryptString_Roundtrip()
     {
@@ -109,8 +123,9 @@ public sealed class RustSdkCipherTests
 
         var encrypted = RustSdkService.EncryptString("secret value", encryptionKey.Key);
 
-        Assert.Throws<RustSdkException>(() =>
+        var ex = Assert.Throws<RustSdkDecryptionException>(() =>
             RustSdkService.DecryptString(encrypted, differentKey.Key));
+        Assert.Equal(RustSdkErrorKind.MacFailure, ex.Kind);
     }
 
     [Fact]
@@ -912,10 +927,31 @@ public sealed class RustSdkCipherTests
         var orgKeys = RustSdkService.GenerateOrganizationKeys();
         var data = "x"u8.ToArray();
 
-        Assert.Throws<RustSdkException>(() =>
+        Assert.Throws<RustSdkInvalidInputException>(() =>
             RustSdkService.EncryptAttachment(data, orgKeys.Key, null, "x.txt", 2));
     }
 
+    [Fact]
+    public void EncryptAttachmentToStream_ProducesBlobThatDecrypts()
+    {
+        var orgKeys = RustSdkService.GenerateOrganizationKeys();
+        // Spans several feed chunks and ends mid-block.
+        var data = new byte[(3 * 1024 * 1024) + 5];
+        new Random(20).NextBytes(data);
+        using var destination = new MemoryStream();
+
+        var enc = RustSdkService.EncryptAttachmentToStream(
+            new MemoryStream(data), orgKeys.Key, null, "large.bin", 1, destination);
+
+        var blob = destination.ToArray();
+        Assert.Equal((byte)2, blob[0]);
+        Assert.Equal(blob.Length, enc.Size);
+        var dec = RustSdkService.DecryptAttachment(blob, enc.Key, enc.FileName, orgKeys.Key, null);
+        Assert.Equal(1, dec.Scheme);
+        Assert.Equal("large.bin", dec.FileName);
+        Assert.Equal(data, dec.Data);
+    }
+
     [Fact]
     public void EncryptFieldsWithCipherKey_EncryptsFieldsUnderCipherKey()
     {
@@ -942,7 +978,30 @@ public sealed class RustSdkCipherTests
         // Fields are encrypted with the cipher key — the vault key alone cannot decrypt them.
         var encryptedName = root.GetProperty("name").GetString()!;
         Assert.StartsWith("2.", encryptedName);
-        Assert.Throws<RustSdkException>(() => RustSdkService.DecryptString(encryptedName, orgKeys.Key));
+        Assert.Throws<RustSdkDecryptionException>(() => RustSdkService.DecryptString(encryptedName, orgKeys.Key));
+    }
+
+    [Fact]
+    public void EncryptMany_EncryptsEachCipherWithItsOwnKeyAndMode()
+    {
+        var userKeys = RustSdkService.GenerateOrganizationKeys();
+        var orgKeys = RustSdkService.GenerateOrganizationKeys();
+
+        var encrypted = CipherEncryption.EncryptMany(
+        [
+            (new CipherViewDto { Name = "Personal Login", Type = CipherTypes.Login }, userKeys.Key, CipherEncryptionType.UserKey),
+            (new CipherViewDto { Name = "Shared Note", Type = CipherTypes.SecureNote }, orgKeys.Key, CipherEncryptionType.CipherKey),
+        ]);
+
+        Assert.Equal(2, encrypted.Count);
+        Assert.Null(encrypted[0].Key);
+        Assert.Equal("Personal Login", RustSdkService.DecryptString(encrypted[0].Name!, userKeys.Key));
+
+        // The cipher-key cipher's fields decrypt only through its wrapped cipher key.
+        Assert.NotNull(encrypted[1].Key);
+        var decrypted = RustSdkService.DecryptFields(
+            JsonSerializer.Serialize(encrypted[1], _sdkJsonOptions), "[\"name\"]", orgKeys.Key, unwrapCipherKey: true);
+        Assert.Equal("Shared Note", JsonDocument.Parse(decrypted).RootElement.GetProperty("name").GetString());
     }
 
     private static string GetWrappedCipherKey(string vaultKey)
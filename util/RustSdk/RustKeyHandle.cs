@@ -0,0 +1,50 @@
+﻿namespace Bit.RustSDK;
+
+/// <summary>
+/// A symmetric key registered with the Rust SDK once and then passed by handle, so it is neither re-parsed
+/// nor sent across the FFI boundary on every call. Dispose it to release the key.
+/// </summary>
+public sealed class RustKeyHandle : IDisposable
+{
+    private ulong _value;
+
+    internal RustKeyHandle(ulong value)
+    {
+        _value = value;
+    }
+
+    ~RustKeyHandle()
+    {
+        Release();
+    }
+
+    /// <summary>
+    /// The raw handle passed to the Rust SDK. Callers must <see cref="GC.KeepAlive"/> the handle after the native
+    /// call, or the finalizer may release the key while the call is still using it.
+    /// </summary>
+    /// <exception cref="ObjectDisposedException">Thrown if the handle has been released.</exception>
+    internal ulong Value
+    {
+        get
+        {
+            var value = Interlocked.Read(ref _value);
+            ObjectDisposedException.ThrowIf(value == 0, this);
+            return value;
+        }
+    }
+
+    public void Dispose()
+    {
+        Release();
+        GC.SuppressFinalize(this);
+    }
+
+    private void Release()
+    {
+        var value = Interlocked.Exchange(ref _value, 0);
+        if (value != 0)
+        {
+            NativeMethods.release_key(value);
+        }
+    }
+}
@@ -1,5 +1,24 @@
 ﻿namespace Bit.RustSDK;
 
+/// <summary>
+/// Machine-readable kinds of Rust SDK errors, matching the <c>status</c> of the FFI result envelope.
+/// </summary>
+public enum RustSdkErrorKind
+{
+    Unknown = 0,
+    InvalidUtf8 = 1,
+    InvalidBase64 = 2,
+    InvalidKey = 3,
+    DecryptionFailed = 4,
+    UnsupportedMode = 5,
+    InvalidInput = 6,
+    CryptoFailure = 7,
+    Panic = 8,
+    InvalidKeyLength = 9,
+    MacFailure = 10,
+    Io = 11,
+}
+
 /// <summary>
 /// Exception thrown when the Rust SDK operations fail
 /// </summary>
@@ -16,4 +35,57 @@ public class RustSdkException : Exception
     public RustSdkException(string message, Exception innerException) : base(message, innerException)
     {
     }
+
+    public RustSdkException(RustSdkErrorKind kind, string message) : base(message)
+    {
+        Kind = kind;
+    }
+
+    /// <summary>
+    /// The kind of error reported by Rust, or <see cref="RustSdkErrorKind.Unknown"/> for errors raised on the .NET side.
+    /// </summary>
+    public RustSdkErrorKind Kind { get; }
+
+    /// <summary>
+    /// Creates the exception for an <c>errorKind</c> from the FFI result envelope.
+    /// </summary>
+    internal static RustSdkException FromErrorKind(string? errorKind, string message)
+    {
+        var kind = Enum.TryParse<RustSdkErrorKind>(errorKind, ignoreCase: true, out var parsed)
+            ? parsed
+            : RustSdkErrorKind.Unknown;
+        message = $"Rust SDK error: {message}";
+
+        return kind switch
+        {
+            RustSdkErrorKind.InvalidUtf8 or RustSdkErrorKind.InvalidBase64 or RustSdkErrorKind.InvalidInput
+                => new RustSdkInvalidInputException(kind, message),
+            RustSdkErrorKind.InvalidKey or RustSdkErrorKind.InvalidKeyLength
+                => new RustSdkInvalidKeyException(kind, message),
+            RustSdkErrorKind.DecryptionFailed or RustSdkErrorKind.MacFailure
+                => new RustSdkDecryptionException(kind, message),
+            RustSdkErrorKind.UnsupportedMode => new RustSdkUnsupportedModeException(message),
+            _ => new RustSdkException(kind, message),
+        };
+    }
 }
+
+/// <summary>
+/// Thrown when an argument passed to the Rust SDK is malformed: invalid UTF-8, invalid base64, or invalid input data.
+/// </summary>
+public class RustSdkInvalidInputException(RustSdkErrorKind kind, string message) : RustSdkException(kind, message);
+
+/// <summary>
+/// Thrown when a key passed to the Rust SDK cannot be parsed or has the wrong length.
+/// </summary>
+public class RustSdkInvalidKeyException(RustSdkErrorKind kind, string message) : RustSdkException(kind, message);
+
+/// <summary>
+/// Thrown when decryption fails. A <see cref="RustSdkErrorKind.MacFailure"/> usually means the wrong key was used.
+/// </summary>
+public class RustSdkDecryptionException(RustSdkErrorKind kind, string message) : RustSdkException(kind, message);
+
+/// <summary>
+/// Thrown when data uses an encryption mode or scheme the Rust SDK does not support.
+/// </summary>
+public class RustSdkUnsupportedModeException(string message) : RustSdkException(RustSdkErrorKind.UnsupportedMode, message);
@@ -1,4 +1,5 @@
-﻿using System.Runtime.InteropServices;
+﻿using System.Runtime.CompilerServices;
+using System.Runtime.InteropServices;
 using System.Text;
 using System.Text.Json;
 
@@ -14,6 +15,97 @@ public class UserKeys
     public required string EncryptedUserKey { get; set; }
     public required string PublicKey { get; set; }
     public required string PrivateKey { get; set; }
+
+    /// <summary>
+    /// The KDF the master key was derived with, as a <c>KdfType</c> value (0 = PBKDF2-SHA256, 1 = Argon2id).
+    /// </summary>
+    public int Kdf { get; set; }
+
+    public int KdfIterations { get; set; }
+
+    /// <summary>
+    /// Argon2id memory in MiB; <c>null</c> for PBKDF2.
+    /// </summary>
+    public int? KdfMemory { get; set; }
+
+    /// <summary>
+    /// Argon2id parallelism; <c>null</c> for PBKDF2.
+    /// </summary>
+    public int? KdfParallelism { get; set; }
+}
+
+/// <summary>
+/// Key material of an account encryption V2 user. <see cref="UserKeys.Key"/> is a COSE
+/// XChaCha20-Poly1305 user key and <see cref="UserKeys.PrivateKey"/> is wrapped by it.
+/// </summary>
+public class UserKeysV2 : UserKeys
+{
+    /// <summary>
+    /// The user's Ed25519 signing key (COSE), wrapped by the user key.
+    /// </summary>
+    public required string SigningKey { get; set; }
+
+    /// <summary>
+    /// Base64 encoded COSE verifying key.
+    /// </summary>
+    public required string VerifyingKey { get; set; }
+
+    /// <summary>
+    /// The user's public key, signed with the signing key.
+    /// </summary>
+    public required string SignedPublicKey { get; set; }
+
+    /// <summary>
+    /// Base64 encoded signed security state, bound to the user ID.
+    /// </summary>
+    public required string SecurityState { get; set; }
+
+    public int SecurityVersion { get; set; }
+}
+
+/// <summary>
+/// One user to generate keys for in <see cref="RustSdkService.GenerateUserKeysBulk"/>, with the arguments of
+/// <see cref="RustSdkService.GenerateUserKeysWithKdf"/>.
+/// </summary>
+public class UserKeysRequest
+{
+    public required string Email { get; init; }
+
+    public required string Password { get; init; }
+
+    /// <summary>A <c>KdfType</c> value: 0 = PBKDF2-SHA256, 1 = Argon2id.</summary>
+    public int Kdf { get; init; }
+
+    public int KdfIterations { get; init; }
+
+    /// <summary>Argon2id memory in MiB; ignored for PBKDF2.</summary>
+    public int? KdfMemory { get; init; }
+
+    /// <summary>Argon2id parallelism; ignored for PBKDF2.</summary>
+    public int? KdfParallelism { get; init; }
+
+    public uint PoolIndex { get; init; }
+}
+
+/// <summary>
+/// Key material of a Key Connector user. There is no master password; <see cref="MasterKey"/> is random and
+/// is what a key-connector stand-in stores and returns on unlock.
+/// </summary>
+public class KeyConnectorUserKeys
+{
+    /// <summary>
+    /// Base64 encoded 32-byte master key.
+    /// </summary>
+    public required string MasterKey { get; set; }
+
+    /// <summary>
+    /// Base64 encoded UserKey
+    /// </summary>
+    public required string Key { get; set; }
+
+    public required string EncryptedUserKey { get; set; }
+    public required string PublicKey { get; set; }
+    public required string PrivateKey { get; set; }
 }
 
 public class OrganizationKeys
@@ -27,6 +119,85 @@ public class OrganizationKeys
     public required string PrivateKey { get; set; }
 }
 
+/// <summary>
+/// A provider's symmetric key and RSA key pair, the private key wrapped by the provider key.
+/// </summary>
+public class ProviderKeys
+{
+    /// <summary>
+    /// Base64 encoded SymmetricCryptoKey
+    /// </summary>
+    public required string Key { get; set; }
+
+    public required string PublicKey { get; set; }
+    public required string PrivateKey { get; set; }
+}
+
+/// <summary>
+/// Key material of a trusted (TDE) device. The encrypted values map onto the <c>Device</c> columns of the
+/// same names.
+/// </summary>
+public class DeviceKeys
+{
+    /// <summary>
+    /// Base64 encoded device key, held only by the device itself.
+    /// </summary>
+    public required string DeviceKey { get; set; }
+
+    /// <summary>
+    /// The user key encapsulated to the device public key.
+    /// </summary>
+    public required string EncryptedUserKey { get; set; }
+
+    /// <summary>
+    /// The device public key encrypted with the user key.
+    /// </summary>
+    public required string EncryptedPublicKey { get; set; }
+
+    /// <summary>
+    /// The device private key encrypted with the device key.
+    /// </summary>
+    public required string EncryptedPrivateKey { get; set; }
+}
+
+/// <summary>
+/// A user's existing user key re-wrapped under a new master password, as an admin account recovery or an
+/// emergency access takeover leaves it. The KDF settings are those the new master key was derived with.
+/// </summary>
+public class RecoveredUserKeys
+{
+    /// <summary>
+    /// Base64 encoded UserKey, unchanged by the recovery.
+    /// </summary>
+    public required string Key { get; set; }
+
+    public required string MasterPasswordHash { get; set; }
+    public required string EncryptedUserKey { get; set; }
+    public int Kdf { get; set; }
+    public int KdfIterations { get; set; }
+    public int? KdfMemory { get; set; }
+    public int? KdfParallelism { get; set; }
+}
+
+/// <summary>
+/// The requesting device's side of a login-with-device auth request. <see cref="PublicKey"/> and
+/// <see cref="AccessCode"/> map onto the <c>AuthRequest</c> columns of the same names.
+/// </summary>
+public class AuthRequestKeys
+{
+    /// <summary>
+    /// Base64 encoded SPKI public key the approval is encapsulated to.
+    /// </summary>
+    public required string PublicKey { get; set; }
+
+    /// <summary>
+    /// Base64 encoded PKCS#8 private key, held only in memory by the requesting device.
+    /// </summary>
+    public required string PrivateKey { get; set; }
+
+    public required string AccessCode { get; set; }
+}
+
 /// <summary>
 /// The result of encrypting an attachment: the encrypted metadata plus the EncArrayBuffer blob to store.
 /// </summary>
@@ -47,173 +218,1102 @@ public class EncryptedAttachment
     public long Size { get; set; }
 }
 
+/// <summary>
+/// The result of streaming an attachment's encryption: the encrypted metadata. The EncArrayBuffer blob was
+/// written to the destination as it was produced.
+/// </summary>
+public class StreamedAttachment
+{
+    /// <summary>
+    /// The wrapped attachment key (EncString), or <c>null</c> for legacy attachments that have no attachment key.
+    /// </summary>
+    public string? Key { get; set; }
+
+    /// <summary>Encrypted filename (EncString).</summary>
+    public required string FileName { get; set; }
+
+    /// <summary>The encrypted blob byte length.</summary>
+    public long Size { get; set; }
+}
+
+/// <summary>
+/// An encrypted Send. <see cref="Key"/> and <see cref="Password"/> map onto the <c>Send</c> columns of the same
+/// names; the remaining EncStrings belong in <c>Send.Data</c>.
+/// </summary>
+public class EncryptedSend
+{
+    /// <summary>
+    /// Base64 encoded 16-byte Send key, carried in the Send link.
+    /// </summary>
+    public required string SendKey { get; set; }
+
+    /// <summary>
+    /// The Send key encrypted with the owner's user key.
+    /// </summary>
+    public required string Key { get; set; }
+
+    public required string Name { get; set; }
+    public string? Notes { get; set; }
+
+    /// <summary>
+    /// Base64 encoded PBKDF2-SHA256 hash of the access password, or <c>null</c> for a Send without one.
+    /// </summary>
+    public string? Password { get; set; }
+}
+
+public class EncryptedTextSend : EncryptedSend
+{
+    public required string Text { get; set; }
+}
+
+public class EncryptedFileSend : EncryptedSend
+{
+    public required string FileName { get; set; }
+
+    /// <summary>The encrypted file bytes in EncArrayBuffer binary layout, to be written to Send file storage.</summary>
+    public required byte[] Data { get; set; }
+
+    /// <summary>The encrypted blob byte length (equals <see cref="Data"/>.Length).</summary>
+    public long Size { get; set; }
+}
+
+/// <summary>
+/// Secrets Manager data encrypted with an organization key. Secrets reference projects by their index in
+/// <see cref="Projects"/>.
+/// </summary>
+public class EncryptedSecretsManagerData
+{
+    public required List<EncryptedProject> Projects { get; set; }
+    public required List<EncryptedSecret> Secrets { get; set; }
+}
+
+public class EncryptedProject
+{
+    public required string Name { get; set; }
+}
+
+public class EncryptedSecret
+{
+    public required string Key { get; set; }
+    public required string Value { get; set; }
+    public required string Note { get; set; }
+    public List<int> ProjectIndexes { get; set; } = [];
+}
+
+/// <summary>
+/// A decrypted attachment, together with the scheme version (0/1/2) that was detected while decrypting it.
+/// </summary>
+public class DecryptedAttachment
+{
+    public int Scheme { get; set; }
+    public required string FileName { get; set; }
+    public required byte[] Data { get; set; }
+}
+
+/// <summary>
+/// The outcome of <see cref="RustSdkService.VerifyVault"/>: how many items were checked and every one that failed
+/// to decrypt.
+/// </summary>
+public class VaultReport
+{
+    public long Checked { get; set; }
+    public List<VaultFailure> Failures { get; set; } = [];
+}
+
+public class VaultFailure
+{
+    /// <summary>
+    /// One of <c>privateKey</c>, <c>organization</c>, <c>cipher</c>, <c>attachment</c>, <c>folder</c> or
+    /// <c>collection</c>.
+    /// </summary>
+    public required string Kind { get; set; }
+
+    public required string Id { get; set; }
+
+    /// <summary>
+    /// The cipher field path that failed, for cipher field failures.
+    /// </summary>
+    public string? Path { get; set; }
+
+    public required string Reason { get; set; }
+}
+
 /// <summary>
 /// Service implementation that provides a C# friendly interface to the Rust SDK
 /// </summary>
 public class RustSdkService
 {
+    /// <summary>
+    /// Plaintext bytes fed to Rust per call when streaming an attachment.
+    /// </summary>
+    private const int AttachmentChunkSize = 1024 * 1024;
+
     private static readonly JsonSerializerOptions CaseInsensitiveOptions = new()
     {
         PropertyNameCaseInsensitive = true
     };
 
+    private static readonly JsonSerializerOptions CamelCaseOptions = new(JsonSerializerDefaults.Web);
+
+    /// <summary>
+    /// The result envelope every Rust FFI function returns.
+    /// </summary>
+    private sealed class RustResponse
+    {
+        public int Status { get; init; }
+
+        public string? ErrorKind { get; init; }
+
+        public string? Message { get; init; }
+
+        public string? Payload { get; init; }
+    }
+
     private sealed class AttachmentResult
     {
         public string? Key { get; init; }
 
-        public string FileName { get; init; } = string.Empty;
+        public string FileName { get; init; } = string.Empty;
+
+        public long Size { get; init; }
+    }
+
+    /// <summary>
+    /// A destination stream, passed to <see cref="WriteToStream"/> through a <see cref="GCHandle"/>.
+    /// </summary>
+    private sealed class StreamSink(Stream destination)
+    {
+        public Stream Destination { get; } = destination;
+
+        public long Origin { get; } = destination.Position;
+    }
+
+    private sealed class DecryptedAttachmentResult
+    {
+        public int Scheme { get; init; }
+
+        public string FileName { get; init; } = string.Empty;
+    }
+
+    private sealed class FileSendResult
+    {
+        public string SendKey { get; init; } = string.Empty;
+
+        public string Key { get; init; } = string.Empty;
+
+        public string Name { get; init; } = string.Empty;
+
+        public string? Notes { get; init; }
+
+        public string? Password { get; init; }
+
+        public string FileName { get; init; } = string.Empty;
+
+        public long Size { get; init; }
+    }
+
+    public static unsafe UserKeys GenerateUserKeys(string email, string password, int kdfIterations = 5_000, uint poolIndex = 0)
+    {
+        var emailBytes = StringToRustString(email);
+        var passwordBytes = StringToRustString(password);
+
+        fixed (byte* emailPtr = emailBytes)
+        fixed (byte* passwordPtr = passwordBytes)
+        {
+            var resultPtr = NativeMethods.generate_user_keys(emailPtr, passwordPtr, (uint)kdfIterations, poolIndex);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<UserKeys>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Generates user keys with an explicit KDF. <paramref name="kdf"/> is a <c>KdfType</c> value
+    /// (0 = PBKDF2-SHA256, 1 = Argon2id); memory (MiB) and parallelism are only used for Argon2id.
+    /// </summary>
+    public static unsafe UserKeys GenerateUserKeysWithKdf(
+        string email,
+        string password,
+        int kdf,
+        int kdfIterations,
+        int? kdfMemory = null,
+        int? kdfParallelism = null,
+        uint poolIndex = 0)
+    {
+        var emailBytes = StringToRustString(email);
+        var passwordBytes = StringToRustString(password);
+
+        fixed (byte* emailPtr = emailBytes)
+        fixed (byte* passwordPtr = passwordBytes)
+        {
+            var resultPtr = NativeMethods.generate_user_keys_with_kdf(
+                emailPtr, passwordPtr, (uint)kdf, (uint)kdfIterations, (uint)(kdfMemory ?? 0),
+                (uint)(kdfParallelism ?? 0), poolIndex);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<UserKeys>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Generates key material for many users in one call, deriving their master keys in parallel across cores,
+    /// so realistic KDF settings stay affordable for large rosters. Returns the keys in input order.
+    /// </summary>
+    public static unsafe List<UserKeys> GenerateUserKeysBulk(IReadOnlyList<UserKeysRequest> users)
+    {
+        var usersBytes = StringToRustString(JsonSerializer.Serialize(users, CamelCaseOptions));
+
+        fixed (byte* usersPtr = usersBytes)
+        {
+            var resultPtr = NativeMethods.generate_user_keys_bulk(usersPtr);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<List<UserKeys>>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Drops the cached master key derivations. User key generation caches each (email, password, KDF)
+    /// derivation, keyed by a hash of the inputs, until the cache fills up or is cleared.
+    /// </summary>
+    public static unsafe void ClearMasterKeyCache()
+    {
+        NativeMethods.clear_master_key_cache();
+    }
+
+    /// <summary>
+    /// Generates the key material of an account encryption V2 user: a COSE XChaCha20-Poly1305 user key,
+    /// a signing key pair, a signed public key and a signed security state bound to <paramref name="userId"/>.
+    /// </summary>
+    public static unsafe UserKeysV2 GenerateUserKeysV2(
+        string email,
+        string password,
+        Guid userId,
+        int kdf = 0,
+        int kdfIterations = 5_000,
+        int? kdfMemory = null,
+        int? kdfParallelism = null,
+        uint poolIndex = 0)
+    {
+        var emailBytes = StringToRustString(email);
+        var passwordBytes = StringToRustString(password);
+        var userIdBytes = StringToRustString(userId.ToString());
+
+        fixed (byte* emailPtr = emailBytes)
+        fixed (byte* passwordPtr = passwordBytes)
+        fixed (byte* userIdPtr = userIdBytes)
+        {
+            var resultPtr = NativeMethods.generate_user_keys_v2(
+                emailPtr, passwordPtr, userIdPtr, (uint)kdf, (uint)kdfIterations, (uint)(kdfMemory ?? 0),
+                (uint)(kdfParallelism ?? 0), poolIndex);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<UserKeysV2>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Generates Key Connector user keys: a random master key wrapping a new user key, plus the usual keypair.
+    /// </summary>
+    public static unsafe KeyConnectorUserKeys GenerateKeyConnectorUserKeys(uint poolIndex = 0)
+    {
+        var resultPtr = NativeMethods.generate_key_connector_user_keys(poolIndex);
+
+        var result = ParseResponse(resultPtr);
+
+        return JsonSerializer.Deserialize<KeyConnectorUserKeys>(result, CaseInsensitiveOptions)!;
+    }
+
+    public static unsafe OrganizationKeys GenerateOrganizationKeys()
+    {
+        var resultPtr = NativeMethods.generate_organization_keys();
+
+        var result = ParseResponse(resultPtr);
+
+        return JsonSerializer.Deserialize<OrganizationKeys>(result, CaseInsensitiveOptions)!;
+    }
+
+    /// <summary>
+    /// Generates an organization key whose RSA keypair is drawn from the pool rather than generated, so
+    /// seeding many organizations does not pay for RSA generation. Organizations share the pool with users:
+    /// offset <paramref name="poolIndex"/> past the users' indexes to keep their keypairs distinct.
+    /// </summary>
+    public static unsafe OrganizationKeys GenerateOrganizationKeys(uint poolIndex)
+    {
+        var resultPtr = NativeMethods.generate_organization_keys_from_pool(poolIndex);
+
+        var result = ParseResponse(resultPtr);
+
+        return JsonSerializer.Deserialize<OrganizationKeys>(result, CaseInsensitiveOptions)!;
+    }
+
+    public static unsafe string GenerateUserOrganizationKey(string userKey, string orgKey)
+    {
+        var userKeyBytes = StringToRustString(userKey);
+        var orgKeyBytes = StringToRustString(orgKey);
+
+        fixed (byte* userKeyPtr = userKeyBytes)
+        fixed (byte* orgKeyPtr = orgKeyBytes)
+        {
+            var resultPtr = NativeMethods.generate_user_organization_key(userKeyPtr, orgKeyPtr);
+
+            var result = ParseResponse(resultPtr);
+
+            return result;
+        }
+    }
+
+    /// <summary>
+    /// Computes the fingerprint phrase a client shows when verifying <paramref name="publicKey"/>: five EFF
+    /// wordlist words joined with <c>-</c>. <paramref name="fingerprintMaterial"/> is usually the key owner's
+    /// user or organization ID.
+    /// </summary>
+    public static unsafe string FingerprintPhrase(string fingerprintMaterial, string publicKey)
+    {
+        var materialBytes = StringToRustString(fingerprintMaterial);
+        var publicKeyBytes = StringToRustString(publicKey);
+
+        fixed (byte* materialPtr = materialBytes)
+        fixed (byte* publicKeyPtr = publicKeyBytes)
+        {
+            var resultPtr = NativeMethods.fingerprint_phrase(materialPtr, publicKeyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Generates a provider's symmetric key with an RSA key pair drawn from the pool at <paramref name="poolIndex"/>.
+    /// </summary>
+    public static unsafe ProviderKeys GenerateProviderKeys(uint poolIndex)
+    {
+        var resultPtr = NativeMethods.generate_provider_keys(poolIndex);
+
+        var result = ParseResponse(resultPtr);
+
+        return JsonSerializer.Deserialize<ProviderKeys>(result, CaseInsensitiveOptions)!;
+    }
+
+    /// <summary>
+    /// Encapsulates a provider key to a user's public key, producing the <c>ProviderUser.Key</c> of a confirmed
+    /// membership.
+    /// </summary>
+    public static unsafe string GenerateProviderUserKey(string userPublicKey, string providerKey)
+    {
+        var userPublicKeyBytes = StringToRustString(userPublicKey);
+        var providerKeyBytes = StringToRustString(providerKey);
+
+        fixed (byte* userPublicKeyPtr = userPublicKeyBytes)
+        fixed (byte* providerKeyPtr = providerKeyBytes)
+        {
+            var resultPtr = NativeMethods.generate_provider_user_key(userPublicKeyPtr, providerKeyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Encapsulates a provider key to every user's public key in one call, in parallel across cores.
+    /// Returns the <c>ProviderUser.Key</c> values in input order.
+    /// </summary>
+    public static unsafe string[] GenerateProviderUserKeys(IReadOnlyList<string> userPublicKeys, string providerKey)
+    {
+        var publicKeysBytes = StringToRustString(JsonSerializer.Serialize(userPublicKeys));
+        var providerKeyBytes = StringToRustString(providerKey);
+
+        fixed (byte* publicKeysPtr = publicKeysBytes)
+        fixed (byte* providerKeyPtr = providerKeyBytes)
+        {
+            var resultPtr = NativeMethods.generate_provider_user_keys(publicKeysPtr, providerKeyPtr);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<string[]>(result)!;
+        }
+    }
+
+    /// <summary>
+    /// Generates the key material a trusted device carries for the user owning <paramref name="userKeyBase64"/>.
+    /// The device keypair is drawn from the SDK's pre-generated RSA pool.
+    /// </summary>
+    public static unsafe DeviceKeys GenerateDeviceKeys(string userKeyBase64, uint poolIndex = 0)
+    {
+        var userKeyBytes = StringToRustString(userKeyBase64);
+
+        fixed (byte* userKeyPtr = userKeyBytes)
+        {
+            var resultPtr = NativeMethods.generate_device_keys(userKeyPtr, poolIndex);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<DeviceKeys>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Enrolls a user in account recovery: encapsulates their user key to the organization public key.
+    /// Returns the <c>OrganizationUser.ResetPasswordKey</c> EncString in format "4.{data}".
+    /// </summary>
+    public static unsafe string GenerateResetPasswordKey(string userKeyBase64, string orgPublicKey)
+    {
+        var userKeyBytes = StringToRustString(userKeyBase64);
+        var orgPublicKeyBytes = StringToRustString(orgPublicKey);
+
+        fixed (byte* userKeyPtr = userKeyBytes)
+        fixed (byte* orgPublicKeyPtr = orgPublicKeyBytes)
+        {
+            var resultPtr = NativeMethods.generate_reset_password_key(userKeyPtr, orgPublicKeyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Simulates an admin recovering an enrolled account: unwraps the organization private key, decapsulates
+    /// the user key from <paramref name="resetPasswordKey"/> and re-wraps it under <paramref name="newPassword"/>.
+    /// </summary>
+    public static unsafe RecoveredUserKeys RecoverAccount(
+        string resetPasswordKey,
+        string orgPrivateKey,
+        string orgKeyBase64,
+        string email,
+        string newPassword,
+        int kdf,
+        int kdfIterations,
+        int? kdfMemory = null,
+        int? kdfParallelism = null)
+    {
+        var resetPasswordKeyBytes = StringToRustString(resetPasswordKey);
+        var orgPrivateKeyBytes = StringToRustString(orgPrivateKey);
+        var orgKeyBytes = StringToRustString(orgKeyBase64);
+        var emailBytes = StringToRustString(email);
+        var newPasswordBytes = StringToRustString(newPassword);
+
+        fixed (byte* resetPasswordKeyPtr = resetPasswordKeyBytes)
+        fixed (byte* orgPrivateKeyPtr = orgPrivateKeyBytes)
+        fixed (byte* orgKeyPtr = orgKeyBytes)
+        fixed (byte* emailPtr = emailBytes)
+        fixed (byte* newPasswordPtr = newPasswordBytes)
+        {
+            var resultPtr = NativeMethods.recover_account(
+                resetPasswordKeyPtr, orgPrivateKeyPtr, orgKeyPtr, emailPtr, newPasswordPtr, (uint)kdf,
+                (uint)kdfIterations, (uint)(kdfMemory ?? 0), (uint)(kdfParallelism ?? 0));
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<RecoveredUserKeys>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Confirms an emergency access grant: encapsulates the grantor's user key to the grantee public key.
+    /// Returns the <c>EmergencyAccess.KeyEncrypted</c> EncString in format "4.{data}".
+    /// </summary>
+    public static unsafe string GenerateEmergencyAccessKey(string grantorUserKeyBase64, string granteePublicKey)
+    {
+        var grantorUserKeyBytes = StringToRustString(grantorUserKeyBase64);
+        var granteePublicKeyBytes = StringToRustString(granteePublicKey);
+
+        fixed (byte* grantorUserKeyPtr = grantorUserKeyBytes)
+        fixed (byte* granteePublicKeyPtr = granteePublicKeyBytes)
+        {
+            var resultPtr = NativeMethods.generate_emergency_access_key(grantorUserKeyPtr, granteePublicKeyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Simulates an emergency access takeover: the grantee unwraps their private key, decapsulates the grantor's
+    /// user key from <paramref name="keyEncrypted"/> and re-wraps it under <paramref name="newPassword"/>.
+    /// </summary>
+    public static unsafe RecoveredUserKeys TakeOverEmergencyAccess(
+        string keyEncrypted,
+        string granteePrivateKey,
+        string granteeUserKeyBase64,
+        string grantorEmail,
+        string newPassword,
+        int kdf,
+        int kdfIterations,
+        int? kdfMemory = null,
+        int? kdfParallelism = null)
+    {
+        var keyEncryptedBytes = StringToRustString(keyEncrypted);
+        var granteePrivateKeyBytes = StringToRustString(granteePrivateKey);
+        var granteeUserKeyBytes = StringToRustString(granteeUserKeyBase64);
+        var grantorEmailBytes = StringToRustString(grantorEmail);
+        var newPasswordBytes = StringToRustString(newPassword);
+
+        fixed (byte* keyEncryptedPtr = keyEncryptedBytes)
+        fixed (byte* granteePrivateKeyPtr = granteePrivateKeyBytes)
+        fixed (byte* granteeUserKeyPtr = granteeUserKeyBytes)
+        fixed (byte* grantorEmailPtr = grantorEmailBytes)
+        fixed (byte* newPasswordPtr = newPasswordBytes)
+        {
+            var resultPtr = NativeMethods.take_over_emergency_access(
+                keyEncryptedPtr, granteePrivateKeyPtr, granteeUserKeyPtr, grantorEmailPtr, newPasswordPtr,
+                (uint)kdf, (uint)kdfIterations, (uint)(kdfMemory ?? 0), (uint)(kdfParallelism ?? 0));
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<RecoveredUserKeys>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Generates the requesting device's ephemeral keypair and access code for an auth request. The keypair is
+    /// drawn from the SDK's pre-generated RSA pool.
+    /// </summary>
+    public static unsafe AuthRequestKeys GenerateAuthRequest(uint poolIndex = 0)
+    {
+        var resultPtr = NativeMethods.generate_auth_request(poolIndex);
+
+        var result = ParseResponse(resultPtr);
+
+        return JsonSerializer.Deserialize<AuthRequestKeys>(result, CaseInsensitiveOptions)!;
+    }
+
+    /// <summary>
+    /// Approves an auth request by encapsulating <paramref name="keyBase64"/> (the user key, or the master key
+    /// for legacy requests) to the request public key. Returns the <c>AuthRequest.Key</c> EncString in format
+    /// "4.{data}".
+    /// </summary>
+    public static unsafe string ApproveAuthRequest(string requestPublicKey, string keyBase64)
+    {
+        var requestPublicKeyBytes = StringToRustString(requestPublicKey);
+        var keyBytes = StringToRustString(keyBase64);
+
+        fixed (byte* requestPublicKeyPtr = requestPublicKeyBytes)
+        fixed (byte* keyPtr = keyBytes)
+        {
+            var resultPtr = NativeMethods.approve_auth_request(requestPublicKeyPtr, keyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Encrypts Secrets Manager projects and secrets in bulk with an organization key. <paramref name="json"/> is
+    /// <c>{ "projects": [name], "secrets": [{ "key", "value", "note", "projectIndexes": [int] }] }</c>.
+    /// </summary>
+    public static unsafe EncryptedSecretsManagerData EncryptSecretsManagerData(string json, string orgKeyBase64)
+    {
+        var jsonBytes = StringToRustString(json);
+        var orgKeyBytes = StringToRustString(orgKeyBase64);
+
+        fixed (byte* jsonPtr = jsonBytes)
+        fixed (byte* orgKeyPtr = orgKeyBytes)
+        {
+            var resultPtr = NativeMethods.encrypt_secrets_manager_data(jsonPtr, orgKeyPtr);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<EncryptedSecretsManagerData>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Generates <paramref name="projectCount"/> projects and <paramref name="secretCount"/> realistic secrets
+    /// (connection strings, API keys, certificates, multi-line values) encrypted with an organization key.
+    /// </summary>
+    public static unsafe EncryptedSecretsManagerData GenerateSecretsManagerData(
+        string orgKeyBase64,
+        uint projectCount,
+        uint secretCount)
+    {
+        var orgKeyBytes = StringToRustString(orgKeyBase64);
+
+        fixed (byte* orgKeyPtr = orgKeyBytes)
+        {
+            var resultPtr = NativeMethods.generate_secrets_manager_data(orgKeyPtr, projectCount, secretCount);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<EncryptedSecretsManagerData>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Encrypts a plaintext string using the provided symmetric key.
+    /// Returns an EncString in format "2.{iv}|{data}|{mac}".
+    /// </summary>
+    public static unsafe string EncryptString(string plaintext, string symmetricKeyBase64)
+    {
+        var plaintextBytes = StringToRustString(plaintext);
+        var keyBytes = StringToRustString(symmetricKeyBase64);
+
+        fixed (byte* plaintextPtr = plaintextBytes)
+        fixed (byte* keyPtr = keyBytes)
+        {
+            var resultPtr = NativeMethods.encrypt_string(plaintextPtr, keyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Wraps a symmetric key with another symmetric key, returning an EncString in format
+    /// "2.{iv}|{data}|{mac}" whose plaintext is the raw key bytes (so it unwraps back into a
+    /// symmetric key). Use this for keys a client unwraps via <c>unwrap_symmetric_key</c> — e.g. a
+    /// <c>ProviderOrganization.Key</c> (an organization key wrapped with the provider's symmetric key).
+    /// Unlike <see cref="EncryptString"/>, this encrypts the key bytes, not the base64 text.
+    /// </summary>
+    public static unsafe string WrapSymmetricKey(string keyToWrapBase64, string wrappingKeyBase64)
+    {
+        var keyToWrapBytes = StringToRustString(keyToWrapBase64);
+        var wrappingKeyBytes = StringToRustString(wrappingKeyBase64);
+
+        fixed (byte* keyToWrapPtr = keyToWrapBytes)
+        fixed (byte* wrappingKeyPtr = wrappingKeyBytes)
+        {
+            var resultPtr = NativeMethods.wrap_symmetric_key(keyToWrapPtr, wrappingKeyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Decrypts an EncString using the provided symmetric key.
+    /// </summary>
+    public static unsafe string DecryptString(string encString, string symmetricKeyBase64)
+    {
+        var encStringBytes = StringToRustString(encString);
+        var keyBytes = StringToRustString(symmetricKeyBase64);
+
+        fixed (byte* encStringPtr = encStringBytes)
+        fixed (byte* keyPtr = keyBytes)
+        {
+            var resultPtr = NativeMethods.decrypt_string(encStringPtr, keyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Encrypts specified fields in a JSON object. Field paths use dot notation
+    /// with [*] for array elements (e.g. "login.uris[*].uri").
+    /// Returns the modified JSON with matching string fields encrypted as EncStrings.
+    /// </summary>
+    public static unsafe string EncryptFields(string json, string fieldPathsJson, string symmetricKeyBase64)
+    {
+        var jsonBytes = StringToRustString(json);
+        var pathsBytes = StringToRustString(fieldPathsJson);
+        var keyBytes = StringToRustString(symmetricKeyBase64);
+
+        fixed (byte* jsonPtr = jsonBytes)
+        fixed (byte* pathsPtr = pathsBytes)
+        fixed (byte* keyPtr = keyBytes)
+        {
+            var resultPtr = NativeMethods.encrypt_fields(jsonPtr, pathsPtr, keyPtr);
+
+            return ParseResponse(resultPtr);
+        }
+    }
+
+    /// <summary>
+    /// Encrypts an attachment's file bytes and filename in one of Bitwarden's attachment scheme versions
+    /// (v0/v1/v2). Returns the encrypted metadata plus the EncArrayBuffer blob.
+    /// </summary>
+    /// <param name="fileBytes">The plaintext file bytes.</param>
+    /// <param name="vaultKeyBase64">Base64-encoded vault key (the user or organization symmetric key).</param>
+    /// <param name="wrappedCipherKey">The cipher's wrapped <c>Key</c> EncString; required for v2, ignored otherwise.</param>
+    /// <param name="fileName">The plaintext filename.</param>
+    /// <param name="version">0 = v0 (no attachment key); 1 = v1 (attachment key wrapped by the vault key); 2 = v2 (attachment key wrapped by the cipher key).</param>
+    public static unsafe EncryptedAttachment EncryptAttachment(
+        byte[] fileBytes,
+        string vaultKeyBase64,
+        string? wrappedCipherKey,
+        string fileName,
+        uint version)
+    {
+        var vaultKeyBytes = StringToRustString(vaultKeyBase64);
+        var wrappedCipherKeyBytes = StringToRustString(wrappedCipherKey ?? string.Empty);
+        var fileNameBytes = StringToRustString(fileName);
+
+        fixed (byte* fileBytesPtr = fileBytes)
+        fixed (byte* vaultKeyPtr = vaultKeyBytes)
+        fixed (byte* wrappedCipherKeyPtr = wrappedCipherKeyBytes)
+        fixed (byte* fileNamePtr = fileNameBytes)
+        {
+            ByteBuffer blob;
+            var resultPtr = NativeMethods.encrypt_attachment_buffer(
+                fileBytesPtr, (nuint)fileBytes.Length, vaultKeyPtr, wrappedCipherKeyPtr, fileNamePtr, version, &blob);
+            var data = TakeByteBuffer(blob);
+
+            var result = ParseResponse(resultPtr);
+
+            var dto = JsonSerializer.Deserialize<AttachmentResult>(result, CaseInsensitiveOptions)
+                ?? throw new RustSdkException("Failed to parse attachment encryption result");
+
+            return new EncryptedAttachment
+            {
+                Key = dto.Key,
+                FileName = dto.FileName,
+                Data = data,
+                Size = dto.Size
+            };
+        }
+    }
+
+    /// <summary>
+    /// Encrypts an attachment read from <paramref name="source"/> in fixed-size chunks, writing the EncArrayBuffer
+    /// blob straight to <paramref name="destinationPath"/>, so neither the file nor its ciphertext is held in memory.
+    /// The blob is identical in layout to <see cref="EncryptAttachment(byte[], string, string?, string, uint)"/>.
+    /// </summary>
+    /// <param name="source">The plaintext file contents, read to the end.</param>
+    /// <param name="vaultKeyBase64">Base64-encoded vault key (the user or organization symmetric key).</param>
+    /// <param name="wrappedCipherKey">The cipher's wrapped <c>Key</c> EncString; required for v2, ignored otherwise.</param>
+    /// <param name="fileName">The plaintext filename.</param>
+    /// <param name="version">0 = v0 (no attachment key); 1 = v1 (attachment key wrapped by the vault key); 2 = v2 (attachment key wrapped by the cipher key).</param>
+    /// <param name="destinationPath">The file to write the blob to; created or truncated.</param>
+    public static unsafe StreamedAttachment EncryptAttachmentToFile(
+        Stream source,
+        string vaultKeyBase64,
+        string? wrappedCipherKey,
+        string fileName,
+        uint version,
+        string destinationPath)
+    {
+        var vaultKeyBytes = StringToRustString(vaultKeyBase64);
+        var wrappedCipherKeyBytes = StringToRustString(wrappedCipherKey ?? string.Empty);
+        var fileNameBytes = StringToRustString(fileName);
+        var pathBytes = StringToRustString(destinationPath);
+
+        ulong handle;
+        fixed (byte* vaultKeyPtr = vaultKeyBytes)
+        fixed (byte* wrappedCipherKeyPtr = wrappedCipherKeyBytes)
+        fixed (byte* fileNamePtr = fileNameBytes)
+        fixed (byte* pathPtr = pathBytes)
+        {
+            var resultPtr = NativeMethods.attachment_stream_init_file(
+                vaultKeyPtr, wrappedCipherKeyPtr, fileNamePtr, version, pathPtr);
+
+            handle = ulong.Parse(ParseResponse(resultPtr));
+        }
+
+        return StreamAttachment(handle, source);
+    }
+
+    /// <summary>
+    /// <see cref="EncryptAttachmentToFile"/>, writing the blob to <paramref name="destination"/> from its current
+    /// position instead. The destination must be seekable: the MAC is written ahead of the ciphertext once the
+    /// whole file has been encrypted.
+    /// </summary>
+    public static unsafe StreamedAttachment EncryptAttachmentToStream(
+        Stream source,
+        string vaultKeyBase64,
+        string? wrappedCipherKey,
+        string fileName,
+        uint version,
+        Stream destination)
+    {
+        if (!destination.CanSeek || !destination.CanWrite)
+        {
+            throw new ArgumentException("The destination stream must be writable and seekable", nameof(destination));
+        }
+
+        var vaultKeyBytes = StringToRustString(vaultKeyBase64);
+        var wrappedCipherKeyBytes = StringToRustString(wrappedCipherKey ?? string.Empty);
+        var fileNameBytes = StringToRustString(fileName);
+
+        var sink = GCHandle.Alloc(new StreamSink(destination));
+        try
+        {
+            ulong handle;
+            fixed (byte* vaultKeyPtr = vaultKeyBytes)
+            fixed (byte* wrappedCipherKeyPtr = wrappedCipherKeyBytes)
+            fixed (byte* fileNamePtr = fileNameBytes)
+            {
+                var resultPtr = NativeMethods.attachment_stream_init_callback(
+                    vaultKeyPtr, wrappedCipherKeyPtr, fileNamePtr, version, &WriteToStream, (void*)GCHandle.ToIntPtr(sink));
 
-        public string Blob { get; init; } = string.Empty;
+                handle = ulong.Parse(ParseResponse(resultPtr));
+            }
 
-        public long Size { get; init; }
+            var attachment = StreamAttachment(handle, source);
+            destination.Position = destination.Length;
+            return attachment;
+        }
+        finally
+        {
+            sink.Free();
+        }
     }
 
-    public static unsafe UserKeys GenerateUserKeys(string email, string password, int kdfIterations = 5_000, uint poolIndex = 0)
+    /// <summary>
+    /// Decrypts an attachment blob and filename, detecting which scheme version (v0/v1/v2) produced them.
+    /// </summary>
+    /// <param name="blob">The EncArrayBuffer blob, as stored.</param>
+    /// <param name="attachmentKey">The attachment's wrapped <c>Key</c>, or <c>null</c> for v0 attachments.</param>
+    /// <param name="encryptedFileName">The attachment's encrypted filename.</param>
+    /// <param name="vaultKeyBase64">Base64-encoded vault key (the user or organization symmetric key).</param>
+    /// <param name="wrappedCipherKey">The cipher's wrapped <c>Key</c> EncString, or <c>null</c> if it has none.</param>
+    public static unsafe DecryptedAttachment DecryptAttachment(
+        byte[] blob,
+        string? attachmentKey,
+        string encryptedFileName,
+        string vaultKeyBase64,
+        string? wrappedCipherKey)
     {
-        var emailBytes = StringToRustString(email);
-        var passwordBytes = StringToRustString(password);
+        var attachmentKeyBytes = StringToRustString(attachmentKey ?? string.Empty);
+        var fileNameBytes = StringToRustString(encryptedFileName);
+        var vaultKeyBytes = StringToRustString(vaultKeyBase64);
+        var wrappedCipherKeyBytes = StringToRustString(wrappedCipherKey ?? string.Empty);
 
-        fixed (byte* emailPtr = emailBytes)
-        fixed (byte* passwordPtr = passwordBytes)
+        fixed (byte* blobPtr = blob)
+        fixed (byte* attachmentKeyPtr = attachmentKeyBytes)
+        fixed (byte* fileNamePtr = fileNameBytes)
+        fixed (byte* vaultKeyPtr = vaultKeyBytes)
+        fixed (byte* wrappedCipherKeyPtr = wrappedCipherKeyBytes)
         {
-            var resultPtr = NativeMethods.generate_user_keys(emailPtr, passwordPtr, (uint)kdfIterations, poolIndex);
+            ByteBuffer plaintext;
+            var resultPtr = NativeMethods.decrypt_attachment_buffer(
+                blobPtr, (nuint)blob.Length, attachmentKeyPtr, fileNamePtr, vaultKeyPtr, wrappedCipherKeyPtr, &plaintext);
+            var data = TakeByteBuffer(plaintext);
 
             var result = ParseResponse(resultPtr);
 
-            return JsonSerializer.Deserialize<UserKeys>(result, CaseInsensitiveOptions)!;
+            var dto = JsonSerializer.Deserialize<DecryptedAttachmentResult>(result, CaseInsensitiveOptions)
+                ?? throw new RustSdkException("Failed to parse attachment decryption result");
+
+            return new DecryptedAttachment
+            {
+                Scheme = dto.Scheme,
+                FileName = dto.FileName,
+                Data = data
+            };
         }
     }
 
-    public static unsafe OrganizationKeys GenerateOrganizationKeys()
+    /// <summary>
+    /// Encrypts a text Send under a fresh Send key for the owner of <paramref name="userKeyBase64"/>.
+    /// Pass <c>null</c> for <paramref name="notes"/> or <paramref name="password"/> to omit them.
+    /// </summary>
+    public static unsafe EncryptedTextSend EncryptTextSend(
+        string userKeyBase64,
+        string name,
+        string? notes,
+        string text,
+        string? password)
     {
-        var resultPtr = NativeMethods.generate_organization_keys();
+        var userKeyBytes = StringToRustString(userKeyBase64);
+        var nameBytes = StringToRustString(name);
+        var notesBytes = StringToRustString(notes ?? string.Empty);
+        var textBytes = StringToRustString(text);
+        var passwordBytes = StringToRustString(password ?? string.Empty);
 
-        var result = ParseResponse(resultPtr);
+        fixed (byte* userKeyPtr = userKeyBytes)
+        fixed (byte* namePtr = nameBytes)
+        fixed (byte* notesPtr = notesBytes)
+        fixed (byte* textPtr = textBytes)
+        fixed (byte* passwordPtr = passwordBytes)
+        {
+            var resultPtr = NativeMethods.encrypt_text_send(userKeyPtr, namePtr, notesPtr, textPtr, passwordPtr);
 
-        return JsonSerializer.Deserialize<OrganizationKeys>(result, CaseInsensitiveOptions)!;
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<EncryptedTextSend>(result, CaseInsensitiveOptions)!;
+        }
     }
 
-    public static unsafe string GenerateUserOrganizationKey(string userKey, string orgKey)
+    /// <summary>
+    /// Encrypts a file Send under a fresh Send key for the owner of <paramref name="userKeyBase64"/>. The file
+    /// bytes are returned in EncArrayBuffer layout, like <see cref="EncryptAttachment(byte[], string, string?, string, uint)"/>.
+    /// </summary>
+    public static unsafe EncryptedFileSend EncryptFileSend(
+        string userKeyBase64,
+        string name,
+        string? notes,
+        byte[] fileBytes,
+        string fileName,
+        string? password)
     {
-        var userKeyBytes = StringToRustString(userKey);
-        var orgKeyBytes = StringToRustString(orgKey);
+        var userKeyBytes = StringToRustString(userKeyBase64);
+        var nameBytes = StringToRustString(name);
+        var notesBytes = StringToRustString(notes ?? string.Empty);
+        var fileNameBytes = StringToRustString(fileName);
+        var passwordBytes = StringToRustString(password ?? string.Empty);
 
         fixed (byte* userKeyPtr = userKeyBytes)
-        fixed (byte* orgKeyPtr = orgKeyBytes)
+        fixed (byte* namePtr = nameBytes)
+        fixed (byte* notesPtr = notesBytes)
+        fixed (byte* fileBytesPtr = fileBytes)
+        fixed (byte* fileNamePtr = fileNameBytes)
+        fixed (byte* passwordPtr = passwordBytes)
         {
-            var resultPtr = NativeMethods.generate_user_organization_key(userKeyPtr, orgKeyPtr);
+            ByteBuffer blob;
+            var resultPtr = NativeMethods.encrypt_file_send(
+                userKeyPtr, namePtr, notesPtr, fileBytesPtr, (nuint)fileBytes.Length, fileNamePtr, passwordPtr, &blob);
+            var data = TakeByteBuffer(blob);
 
             var result = ParseResponse(resultPtr);
 
-            return result;
+            var dto = JsonSerializer.Deserialize<FileSendResult>(result, CaseInsensitiveOptions)
+                ?? throw new RustSdkException("Failed to parse file send encryption result");
+
+            return new EncryptedFileSend
+            {
+                SendKey = dto.SendKey,
+                Key = dto.Key,
+                Name = dto.Name,
+                Notes = dto.Notes,
+                Password = dto.Password,
+                FileName = dto.FileName,
+                Data = data,
+                Size = dto.Size
+            };
         }
     }
 
     /// <summary>
-    /// Encrypts a plaintext string using the provided symmetric key.
-    /// Returns an EncString in format "2.{iv}|{data}|{mac}".
+    /// Encrypts specified JSON fields under a freshly generated per-cipher key and returns the modified
+    /// JSON with the cipher key (wrapped by the vault key) injected as the top-level <c>key</c> field.
+    /// Use this to produce a "cipher key" cipher; use <see cref="EncryptFields"/> for a user-key cipher.
     /// </summary>
-    public static unsafe string EncryptString(string plaintext, string symmetricKeyBase64)
+    public static unsafe string EncryptFieldsWithCipherKey(string json, string fieldPathsJson, string symmetricKeyBase64)
     {
-        var plaintextBytes = StringToRustString(plaintext);
+        var jsonBytes = StringToRustString(json);
+        var pathsBytes = StringToRustString(fieldPathsJson);
         var keyBytes = StringToRustString(symmetricKeyBase64);
 
-        fixed (byte* plaintextPtr = plaintextBytes)
+        fixed (byte* jsonPtr = jsonBytes)
+        fixed (byte* pathsPtr = pathsBytes)
         fixed (byte* keyPtr = keyBytes)
         {
-            var resultPtr = NativeMethods.encrypt_string(plaintextPtr, keyPtr);
+            var resultPtr = NativeMethods.encrypt_fields_with_cipher_key(jsonPtr, pathsPtr, keyPtr);
 
             return ParseResponse(resultPtr);
         }
     }
 
     /// <summary>
-    /// Wraps a symmetric key with another symmetric key, returning an EncString in format
-    /// "2.{iv}|{data}|{mac}" whose plaintext is the raw key bytes (so it unwraps back into a
-    /// symmetric key). Use this for keys a client unwraps via <c>unwrap_symmetric_key</c> — e.g. a
-    /// <c>ProviderOrganization.Key</c> (an organization key wrapped with the provider's symmetric key).
-    /// Unlike <see cref="EncryptString"/>, this encrypts the key bytes, not the base64 text.
+    /// Decrypts specified fields in a JSON object, the inverse of <see cref="EncryptFields"/> and
+    /// <see cref="EncryptFieldsWithCipherKey"/>. When <paramref name="unwrapCipherKey"/> is set and the object has a
+    /// top-level <c>key</c>, the fields are decrypted with that cipher key, unwrapped with the vault key.
+    /// Returns the plaintext JSON view.
     /// </summary>
-    public static unsafe string WrapSymmetricKey(string keyToWrapBase64, string wrappingKeyBase64)
+    public static unsafe string DecryptFields(
+        string json,
+        string fieldPathsJson,
+        string symmetricKeyBase64,
+        bool unwrapCipherKey = false)
     {
-        var keyToWrapBytes = StringToRustString(keyToWrapBase64);
-        var wrappingKeyBytes = StringToRustString(wrappingKeyBase64);
+        var jsonBytes = StringToRustString(json);
+        var pathsBytes = StringToRustString(fieldPathsJson);
+        var keyBytes = StringToRustString(symmetricKeyBase64);
 
-        fixed (byte* keyToWrapPtr = keyToWrapBytes)
-        fixed (byte* wrappingKeyPtr = wrappingKeyBytes)
+        fixed (byte* jsonPtr = jsonBytes)
+        fixed (byte* pathsPtr = pathsBytes)
+        fixed (byte* keyPtr = keyBytes)
         {
-            var resultPtr = NativeMethods.wrap_symmetric_key(keyToWrapPtr, wrappingKeyPtr);
+            var resultPtr = NativeMethods.decrypt_fields(jsonPtr, pathsPtr, keyPtr, unwrapCipherKey);
 
             return ParseResponse(resultPtr);
         }
     }
 
     /// <summary>
-    /// Decrypts an EncString using the provided symmetric key.
+    /// Encrypts a batch of cipher views in one call, in parallel across cores. Each element of
+    /// <paramref name="ciphersJson"/> is <c>{ "view", "key", "mode" }</c>, where <c>mode</c> is 0 for a user-key cipher
+    /// (as <see cref="EncryptFields"/>) or 1 for a cipher-key cipher (as <see cref="EncryptFieldsWithCipherKey"/>).
+    /// Returns a JSON array of the encrypted cipher objects in input order.
     /// </summary>
-    public static unsafe string DecryptString(string encString, string symmetricKeyBase64)
+    public static unsafe string EncryptCiphersBulk(string ciphersJson, string fieldPathsJson)
     {
-        var encStringBytes = StringToRustString(encString);
-        var keyBytes = StringToRustString(symmetricKeyBase64);
+        var ciphersBytes = StringToRustString(ciphersJson);
+        var pathsBytes = StringToRustString(fieldPathsJson);
 
-        fixed (byte* encStringPtr = encStringBytes)
-        fixed (byte* keyPtr = keyBytes)
+        fixed (byte* ciphersPtr = ciphersBytes)
+        fixed (byte* pathsPtr = pathsBytes)
         {
-            var resultPtr = NativeMethods.decrypt_string(encStringPtr, keyPtr);
+            var resultPtr = NativeMethods.encrypt_ciphers_bulk(ciphersPtr, pathsPtr);
 
             return ParseResponse(resultPtr);
         }
     }
 
     /// <summary>
-    /// Encrypts specified fields in a JSON object. Field paths use dot notation
-    /// with [*] for array elements (e.g. "login.uris[*].uri").
-    /// Returns the modified JSON with matching string fields encrypted as EncStrings.
+    /// Registers a symmetric key with the Rust SDK and returns a handle the handle overloads accept in its place.
+    /// Register keys that are reused many times, such as an organization key; dispose the handle to release the key.
     /// </summary>
-    public static unsafe string EncryptFields(string json, string fieldPathsJson, string symmetricKeyBase64)
+    public static unsafe RustKeyHandle RegisterKey(string symmetricKeyBase64)
     {
-        var jsonBytes = StringToRustString(json);
-        var pathsBytes = StringToRustString(fieldPathsJson);
         var keyBytes = StringToRustString(symmetricKeyBase64);
 
-        fixed (byte* jsonPtr = jsonBytes)
-        fixed (byte* pathsPtr = pathsBytes)
         fixed (byte* keyPtr = keyBytes)
         {
-            var resultPtr = NativeMethods.encrypt_fields(jsonPtr, pathsPtr, keyPtr);
+            var resultPtr = NativeMethods.register_key(keyPtr);
+
+            return new RustKeyHandle(ulong.Parse(ParseResponse(resultPtr)));
+        }
+    }
+
+    /// <summary>
+    /// <see cref="EncryptString(string, string)"/> with a registered key.
+    /// </summary>
+    public static unsafe string EncryptString(string plaintext, RustKeyHandle key)
+    {
+        var plaintextBytes = StringToRustString(plaintext);
+
+        fixed (byte* plaintextPtr = plaintextBytes)
+        {
+            var resultPtr = NativeMethods.encrypt_string_with_handle(plaintextPtr, key.Value);
+            GC.KeepAlive(key);
 
             return ParseResponse(resultPtr);
         }
     }
 
     /// <summary>
-    /// Encrypts an attachment's file bytes and filename in one of Bitwarden's attachment scheme versions
-    /// (v0/v1/v2). Returns the encrypted metadata plus the EncArrayBuffer blob.
+    /// <see cref="EncryptFields(string, string, string)"/> with a registered key.
+    /// </summary>
+    public static string EncryptFields(string json, string fieldPathsJson, RustKeyHandle key) =>
+        EncryptFieldsWithHandle(json, fieldPathsJson, key, cipherKeyMode: 0);
+
+    /// <summary>
+    /// <see cref="EncryptFieldsWithCipherKey(string, string, string)"/> with a registered vault key.
+    /// </summary>
+    public static string EncryptFieldsWithCipherKey(string json, string fieldPathsJson, RustKeyHandle key) =>
+        EncryptFieldsWithHandle(json, fieldPathsJson, key, cipherKeyMode: 1);
+
+    /// <summary>
+    /// <see cref="WrapSymmetricKey(string, string)"/> with registered keys.
+    /// </summary>
+    public static unsafe string WrapSymmetricKey(RustKeyHandle keyToWrap, RustKeyHandle wrappingKey)
+    {
+        var resultPtr = NativeMethods.wrap_symmetric_key_with_handles(keyToWrap.Value, wrappingKey.Value);
+        GC.KeepAlive(keyToWrap);
+        GC.KeepAlive(wrappingKey);
+
+        return ParseResponse(resultPtr);
+    }
+
+    /// <summary>
+    /// <see cref="EncryptAttachment(byte[], string, string?, string, uint)"/> with a registered vault key.
     /// </summary>
-    /// <param name="fileBytes">The plaintext file bytes.</param>
-    /// <param name="vaultKeyBase64">Base64-encoded vault key (the user or organization symmetric key).</param>
-    /// <param name="wrappedCipherKey">The cipher's wrapped <c>Key</c> EncString; required for v2, ignored otherwise.</param>
-    /// <param name="fileName">The plaintext filename.</param>
-    /// <param name="version">0 = v0 (no attachment key); 1 = v1 (attachment key wrapped by the vault key); 2 = v2 (attachment key wrapped by the cipher key).</param>
     public static unsafe EncryptedAttachment EncryptAttachment(
         byte[] fileBytes,
-        string vaultKeyBase64,
+        RustKeyHandle vaultKey,
         string? wrappedCipherKey,
         string fileName,
         uint version)
     {
-        var fileBytesInput = StringToRustString(Convert.ToBase64String(fileBytes));
-        var vaultKeyBytes = StringToRustString(vaultKeyBase64);
         var wrappedCipherKeyBytes = StringToRustString(wrappedCipherKey ?? string.Empty);
         var fileNameBytes = StringToRustString(fileName);
 
-        fixed (byte* fileBytesPtr = fileBytesInput)
-        fixed (byte* vaultKeyPtr = vaultKeyBytes)
+        fixed (byte* fileBytesPtr = fileBytes)
         fixed (byte* wrappedCipherKeyPtr = wrappedCipherKeyBytes)
         fixed (byte* fileNamePtr = fileNameBytes)
         {
-            var resultPtr = NativeMethods.encrypt_attachment(
-                fileBytesPtr, vaultKeyPtr, wrappedCipherKeyPtr, fileNamePtr, version);
+            ByteBuffer blob;
+            var resultPtr = NativeMethods.encrypt_attachment_with_handle(
+                fileBytesPtr, (nuint)fileBytes.Length, vaultKey.Value, wrappedCipherKeyPtr, fileNamePtr, version, &blob);
+            GC.KeepAlive(vaultKey);
+            var data = TakeByteBuffer(blob);
 
             var result = ParseResponse(resultPtr);
 
@@ -224,44 +1324,209 @@ public class RustSdkService
             {
                 Key = dto.Key,
                 FileName = dto.FileName,
-                Data = Convert.FromBase64String(dto.Blob),
+                Data = data,
                 Size = dto.Size
             };
         }
     }
 
-    /// <summary>
-    /// Encrypts specified JSON fields under a freshly generated per-cipher key and returns the modified
-    /// JSON with the cipher key (wrapped by the vault key) injected as the top-level <c>key</c> field.
-    /// Use this to produce a "cipher key" cipher; use <see cref="EncryptFields"/> for a user-key cipher.
-    /// </summary>
-    public static unsafe string EncryptFieldsWithCipherKey(string json, string fieldPathsJson, string symmetricKeyBase64)
+    private static unsafe string EncryptFieldsWithHandle(
+        string json,
+        string fieldPathsJson,
+        RustKeyHandle key,
+        uint cipherKeyMode)
     {
         var jsonBytes = StringToRustString(json);
         var pathsBytes = StringToRustString(fieldPathsJson);
-        var keyBytes = StringToRustString(symmetricKeyBase64);
 
         fixed (byte* jsonPtr = jsonBytes)
         fixed (byte* pathsPtr = pathsBytes)
-        fixed (byte* keyPtr = keyBytes)
         {
-            var resultPtr = NativeMethods.encrypt_fields_with_cipher_key(jsonPtr, pathsPtr, keyPtr);
+            var resultPtr = NativeMethods.encrypt_fields_with_handle(jsonPtr, pathsPtr, key.Value, cipherKeyMode);
+            GC.KeepAlive(key);
 
             return ParseResponse(resultPtr);
         }
     }
 
+    /// <summary>
+    /// Tries to decrypt everything seeded for one user — organization keys, ciphers and their cipher keys,
+    /// attachments, folders and collections — and reports every item that fails, with the reason.
+    /// See <c>verify_vault</c> in <c>vault_check.rs</c> for the shape of <paramref name="vaultJson"/>.
+    /// </summary>
+    public static unsafe VaultReport VerifyVault(string vaultJson)
+    {
+        var vaultJsonBytes = StringToRustString(vaultJson);
+
+        fixed (byte* vaultJsonPtr = vaultJsonBytes)
+        {
+            var resultPtr = NativeMethods.verify_vault(vaultJsonPtr);
+
+            var result = ParseResponse(resultPtr);
+
+            return JsonSerializer.Deserialize<VaultReport>(result, CaseInsensitiveOptions)!;
+        }
+    }
+
+    /// <summary>
+    /// Switches all key, IV and keypair generation to a deterministic stream seeded with
+    /// <paramref name="seed"/>, so repeated runs issuing the same calls in the same order produce
+    /// byte-identical output. RSA-OAEP encapsulations (e.g. <see cref="GenerateUserOrganizationKey"/>)
+    /// remain randomized. Process-wide; call <see cref="ClearRngSeed"/> to return to the OS RNG.
+    /// </summary>
+    public static unsafe void SetRngSeed(ulong seed)
+    {
+        NativeMethods.set_rng_seed(seed);
+    }
+
+    /// <summary>
+    /// Switches key, IV and keypair generation back to the OS RNG.
+    /// </summary>
+    public static unsafe void ClearRngSeed()
+    {
+        NativeMethods.clear_rng_seed();
+    }
+
+    /// <summary>
+    /// Replaces the RSA keypair pool that <c>poolIndex</c> selects from with the PKCS#8 PEM keys in
+    /// <paramref name="path"/>: a file of concatenated keys, or a directory of <c>.pem</c> files taken in name
+    /// order. Process-wide; load the pool once, before seeding. Returns the pool size.
+    /// </summary>
+    public static unsafe int LoadRsaPool(string path)
+    {
+        var pathBytes = StringToRustString(path);
+
+        fixed (byte* pathPtr = pathBytes)
+        {
+            var resultPtr = NativeMethods.load_rsa_pool(pathPtr);
+
+            return int.Parse(ParseResponse(resultPtr));
+        }
+    }
+
+    /// <summary>
+    /// Replaces the RSA keypair pool with <paramref name="size"/> keys from the PEM cache file at
+    /// <paramref name="cachePath"/>, generating and caching whichever keys it is missing, so only the first run
+    /// pays for RSA generation. Returns the pool size.
+    /// </summary>
+    public static unsafe int LoadOrGenerateRsaPool(string cachePath, int size)
+    {
+        ArgumentOutOfRangeException.ThrowIfNegativeOrZero(size);
+
+        var cachePathBytes = StringToRustString(cachePath);
+
+        fixed (byte* cachePathPtr = cachePathBytes)
+        {
+            var resultPtr = NativeMethods.load_or_generate_rsa_pool(cachePathPtr, (uint)size);
+
+            return int.Parse(ParseResponse(resultPtr));
+        }
+    }
+
+    /// <summary>
+    /// Switches back to the 100 compiled-in RSA keypairs.
+    /// </summary>
+    public static unsafe void ResetRsaPool()
+    {
+        NativeMethods.reset_rsa_pool();
+    }
+
+    /// <summary>
+    /// The number of RSA keypairs in the active pool; <c>poolIndex</c> values wrap modulo this size.
+    /// </summary>
+    public static unsafe int RsaPoolSize()
+    {
+        return (int)NativeMethods.rsa_pool_size();
+    }
+
+    /// <summary>
+    /// Feeds <paramref name="source"/> to an open attachment stream and finalizes it, aborting the stream if
+    /// anything fails.
+    /// </summary>
+    private static unsafe StreamedAttachment StreamAttachment(ulong handle, Stream source)
+    {
+        try
+        {
+            var chunk = new byte[AttachmentChunkSize];
+            int read;
+            while ((read = source.Read(chunk, 0, chunk.Length)) > 0)
+            {
+                fixed (byte* chunkPtr = chunk)
+                {
+                    ParseResponse(NativeMethods.attachment_stream_feed(handle, chunkPtr, (nuint)read));
+                }
+            }
+
+            var result = ParseResponse(NativeMethods.attachment_stream_finalize(handle));
+
+            var dto = JsonSerializer.Deserialize<AttachmentResult>(result, CaseInsensitiveOptions)
+                ?? throw new RustSdkException("Failed to parse attachment encryption result");
+
+            return new StreamedAttachment
+            {
+                Key = dto.Key,
+                FileName = dto.FileName,
+                Size = dto.Size
+            };
+        }
+        catch
+        {
+            NativeMethods.attachment_stream_abort(handle);
+            throw;
+        }
+    }
+
+    /// <summary>
+    /// Write callback for <c>attachment_stream_init_callback</c>: writes <paramref name="len"/> bytes at
+    /// <paramref name="offset"/> past the <see cref="StreamSink"/>'s origin. Returns 0 on success.
+    /// </summary>
+    [UnmanagedCallersOnly(CallConvs = [typeof(CallConvCdecl)])]
+    private static unsafe int WriteToStream(void* context, ulong offset, byte* data, nuint len)
+    {
+        try
+        {
+            var sink = (StreamSink)GCHandle.FromIntPtr((IntPtr)context).Target!;
+            sink.Destination.Position = sink.Origin + checked((long)offset);
+            sink.Destination.Write(new ReadOnlySpan<byte>(data, checked((int)len)));
+            return 0;
+        }
+        catch
+        {
+            return 1;
+        }
+    }
+
     private static byte[] StringToRustString(string str)
     {
         return Encoding.UTF8.GetBytes(str + '\0');
     }
 
     /// <summary>
-    /// Parses a response from Rust FFI, checks for errors, and frees the native string.
+    /// Copies a byte buffer returned from Rust FFI into managed memory and frees the native buffer.
+    /// An empty buffer (as written on failure) yields an empty array.
+    /// </summary>
+    private static unsafe byte[] TakeByteBuffer(ByteBuffer buffer)
+    {
+        try
+        {
+            return buffer.data == null ? [] : new ReadOnlySpan<byte>(buffer.data, checked((int)buffer.len)).ToArray();
+        }
+        finally
+        {
+            NativeMethods.free_byte_buffer(buffer);
+        }
+    }
+
+    /// <summary>
+    /// Parses the result envelope returned from Rust FFI, frees the native string, and returns its payload.
     /// </summary>
+    /// <remarks>
+    /// Every FFI function returns <c>{ "status", "errorKind", "message", "payload" }</c>. A status of 0 means
+    /// success; anything else is mapped to a <see cref="RustSdkException"/> subclass by its error kind.
+    /// </remarks>
     /// <param name="ptr">Pointer to the C string returned from Rust</param>
-    /// <returns>The parsed response string</returns>
-    /// <exception cref="RustSdkException">Thrown if the pointer is null, conversion fails, or the response contains an error</exception>
+    /// <returns>The payload string</returns>
+    /// <exception cref="RustSdkException">Thrown if the pointer is null, conversion fails, or the envelope reports an error</exception>
     private static unsafe string ParseResponse(byte* ptr)
     {
         if (ptr == null)
@@ -277,28 +1542,23 @@ public class RustSdkService
             throw new RustSdkException("Failed to convert native result to string");
         }
 
-        // Check if response is an error from Rust
-        // Rust error responses follow the format: {"error": "message"}
-        if (result.TrimStart().StartsWith('{') && result.Contains("\"error\"", StringComparison.Ordinal))
+        RustResponse response;
+        try
         {
-            try
-            {
-                using var doc = JsonDocument.Parse(result);
-                if (doc.RootElement.TryGetProperty("error", out var errorElement))
-                {
-                    var errorMessage = errorElement.GetString();
-                    if (!string.IsNullOrEmpty(errorMessage))
-                    {
-                        throw new RustSdkException($"Rust SDK error: {errorMessage}");
-                    }
-                }
-            }
-            catch (JsonException)
-            {
-                // If we can't parse it as an error, it's likely valid data that happens to contain "error"
-            }
+            response = JsonSerializer.Deserialize<RustResponse>(result, CaseInsensitiveOptions)
+                ?? throw new RustSdkException("Rust SDK returned an empty response");
+        }
+        catch (JsonException ex)
+        {
+            throw new RustSdkException("Failed to parse Rust SDK response", ex);
+        }
+
+        if (response.Status != 0)
+        {
+            throw RustSdkException.FromErrorKind(response.ErrorKind, response.Message ?? "Unknown error");
         }
 
-        return result;
+        return response.Payload
+            ?? throw new RustSdkException("Rust SDK response is missing its payload");
     }
 }
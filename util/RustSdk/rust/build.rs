@@ -1,9 +1,23 @@
 fn main() {
     csbindgen::Builder::default()
         .input_extern_file("src/lib.rs")
+        .input_extern_file("src/account_recovery.rs")
+        .input_extern_file("src/account_v2.rs")
         .input_extern_file("src/cipher.rs")
         .input_extern_file("src/attachment.rs")
+        .input_extern_file("src/attachment_stream.rs")
+        .input_extern_file("src/auth_request.rs")
+        .input_extern_file("src/device.rs")
+        .input_extern_file("src/emergency_access.rs")
+        .input_extern_file("src/fingerprint.rs")
+        .input_extern_file("src/key_handle.rs")
+        .input_extern_file("src/master_key_cache.rs")
         .input_extern_file("src/provider.rs")
+        .input_extern_file("src/rng.rs")
+        .input_extern_file("src/rsa_pool.rs")
+        .input_extern_file("src/secrets_manager.rs")
+        .input_extern_file("src/send.rs")
+        .input_extern_file("src/vault_check.rs")
         .csharp_dll_name("libsdk")
         .csharp_namespace("Bit.RustSDK")
         .csharp_class_accessibility("public")
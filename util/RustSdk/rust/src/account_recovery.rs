@@ -0,0 +1,234 @@
+//! Admin account recovery (reset password) key material for the Seeder.
+//!
+//! Enrolling in account recovery stores `OrganizationUser.ResetPasswordKey`: the user's user key
+//! encapsulated to the organization's public key. An admin later recovers the account by unwrapping
+//! the organization private key, decapsulating the user key, and wrapping it under a new master
+//! password. Both sides run through the same `bitwarden_crypto` primitives real clients use.
+
+use std::ffi::{c_char, CStr};
+
+use bitwarden_crypto::Kdf;
+
+use crate::crypto_util::{
+    decapsulate_key, encapsulate_key, error_response, ffi_boundary, kdf_columns, make_kdf,
+    parse_key, parse_public_key, success_response, unwrap_private_key, wrap_user_key_with_password,
+    SdkError,
+};
+
+/// Enroll a user in account recovery: encapsulate their user key to the organization public key.
+///
+/// # Arguments
+/// * `user_key_b64` - Base64-encoded user key of the enrolling user
+/// * `organization_public_key_b64` - Base64-encoded SPKI public key of the organization
+///
+/// # Returns
+/// The `OrganizationUser.ResetPasswordKey` EncString, in format "4.{data}"
+///
+/// # Safety
+/// Both pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn generate_reset_password_key(
+    user_key_b64: *const c_char,
+    organization_public_key_b64: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(user_key_b64) = CStr::from_ptr(user_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("user_key_b64"));
+        };
+        let Ok(organization_public_key_b64) = CStr::from_ptr(organization_public_key_b64).to_str()
+        else {
+            return error_response(SdkError::invalid_utf8("organization_public_key_b64"));
+        };
+
+        let result = parse_key(user_key_b64).and_then(|user_key| {
+            encapsulate_key(&user_key, &parse_public_key(organization_public_key_b64)?)
+        });
+
+        match result {
+            Ok(reset_password_key) => success_response(&reset_password_key),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Simulate an admin recovering an enrolled account under a new master password.
+///
+/// # Arguments
+/// * `reset_password_key` - The user's `OrganizationUser.ResetPasswordKey`
+/// * `organization_private_key` - The organization's `PrivateKey` EncString, wrapped by the organization key
+/// * `organization_key_b64` - Base64-encoded organization symmetric key
+/// * `email` - The recovered user's email, used as the master key salt
+/// * `new_password` - The new master password the admin sets
+/// * `kdf_type`, `kdf_iterations`, `kdf_memory`, `kdf_parallelism` - The user's KDF settings
+///
+/// # Returns
+/// JSON `{ "key": <base64 user key>, "masterPasswordHash": <hash>, "encryptedUserKey": <EncString>,
+/// "kdf", "kdfIterations", "kdfMemory", "kdfParallelism" }`, where the user key is unchanged and is
+/// now wrapped by the new master key.
+///
+/// # Safety
+/// All pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn recover_account(
+    reset_password_key: *const c_char,
+    organization_private_key: *const c_char,
+    organization_key_b64: *const c_char,
+    email: *const c_char,
+    new_password: *const c_char,
+    kdf_type: u32,
+    kdf_iterations: u32,
+    kdf_memory: u32,
+    kdf_parallelism: u32,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(reset_password_key) = CStr::from_ptr(reset_password_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("reset_password_key"));
+        };
+        let Ok(organization_private_key) = CStr::from_ptr(organization_private_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("organization_private_key"));
+        };
+        let Ok(organization_key_b64) = CStr::from_ptr(organization_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("organization_key_b64"));
+        };
+        let Ok(email) = CStr::from_ptr(email).to_str() else {
+            return error_response(SdkError::invalid_utf8("email"));
+        };
+        let Ok(new_password) = CStr::from_ptr(new_password).to_str() else {
+            return error_response(SdkError::invalid_utf8("new_password"));
+        };
+
+        let result =
+            make_kdf(kdf_type, kdf_iterations, kdf_memory, kdf_parallelism).and_then(|kdf| {
+                recover_account_internal(
+                    reset_password_key,
+                    organization_private_key,
+                    organization_key_b64,
+                    email,
+                    new_password,
+                    &kdf,
+                )
+            });
+
+        match result {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Decapsulate a user key with a wrapped RSA private key and re-wrap it under a new master password.
+///
+/// Shared by admin account recovery and emergency access takeover, which differ only in whose
+/// keypair the user key was encapsulated to.
+pub(crate) fn recover_account_internal(
+    encapsulated_user_key: &str,
+    wrapped_private_key: &str,
+    private_key_wrapping_key_b64: &str,
+    email: &str,
+    new_password: &str,
+    kdf: &Kdf,
+) -> Result<String, SdkError> {
+    let wrapping_key = parse_key(private_key_wrapping_key_b64)?;
+    let private_key = unwrap_private_key(wrapped_private_key, &wrapping_key)?;
+    let user_key = decapsulate_key(encapsulated_user_key, &private_key)?;
+
+    let (master_password_hash, encrypted_user_key) =
+        wrap_user_key_with_password(email, new_password, kdf, &user_key)?;
+    let (kdf_type, kdf_iterations, kdf_memory, kdf_parallelism) = kdf_columns(kdf);
+
+    let result = serde_json::json!({
+        "key": user_key.to_base64(),
+        "masterPasswordHash": master_password_hash,
+        "encryptedUserKey": encrypted_user_key,
+        "kdf": kdf_type,
+        "kdfIterations": kdf_iterations,
+        "kdfMemory": kdf_memory,
+        "kdfParallelism": kdf_parallelism,
+    });
+
+    serde_json::to_string(&result)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize recovered keys"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::{make_test_key, take_response, ErrorKind, KDF_PBKDF2};
+    use crate::{keypair, pool_material};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bitwarden_crypto::{EncString, MasterKey, PrivateKey};
+    use std::ffi::CString;
+
+    #[test]
+    fn reset_password_key_decapsulates_with_organization_private_key() {
+        let user_key = make_test_key();
+        let user_key_b64: String = user_key.to_base64().into();
+        let public_key_b64 = STANDARD.encode(pool_material(11).public_der.as_ref());
+
+        let user_key_ptr = CString::new(user_key_b64.clone()).unwrap();
+        let public_key_ptr = CString::new(public_key_b64).unwrap();
+        let ptr =
+            unsafe { generate_reset_password_key(user_key_ptr.as_ptr(), public_key_ptr.as_ptr()) };
+        let reset_password_key = take_response(ptr).unwrap();
+
+        let private_key = PrivateKey::from_der(&pool_material(11).private_der).unwrap();
+        let recovered = decapsulate_key(&reset_password_key, &private_key).unwrap();
+        assert_eq!(<String>::from(recovered.to_base64()), user_key_b64);
+    }
+
+    #[test]
+    fn recover_account_rewraps_user_key_under_new_password() {
+        let organization_key = make_test_key();
+        let organization_key_b64: String = organization_key.to_base64().into();
+        let organization_keypair = keypair(&organization_key, 11).unwrap();
+
+        let user_key = make_test_key();
+        let user_key_b64: String = user_key.to_base64().into();
+        let public_key = parse_public_key(&organization_keypair.public.to_string()).unwrap();
+        let reset_password_key = encapsulate_key(&user_key, &public_key).unwrap();
+
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        let json = recover_account_internal(
+            &reset_password_key,
+            &organization_keypair.private.to_string(),
+            &organization_key_b64,
+            "recovered@example.com",
+            "new-master-password",
+            &kdf,
+        )
+        .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // The user key is unchanged, now wrapped by the master key of the new password.
+        assert_eq!(parsed["key"].as_str().unwrap(), user_key_b64);
+        let master_key =
+            MasterKey::derive("new-master-password", "recovered@example.com", &kdf).unwrap();
+        let encrypted: EncString = parsed["encryptedUserKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let unwrapped = master_key.decrypt_user_key(encrypted).unwrap();
+        assert_eq!(<String>::from(unwrapped.to_base64()), user_key_b64);
+    }
+
+    #[test]
+    fn recover_account_rejects_wrong_organization_key() {
+        let organization_key = make_test_key();
+        let organization_keypair = keypair(&organization_key, 11).unwrap();
+        let other_key_b64: String = make_test_key().to_base64().into();
+
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        let err = recover_account_internal(
+            "4.unused",
+            &organization_keypair.private.to_string(),
+            &other_key_b64,
+            "recovered@example.com",
+            "new-master-password",
+            &kdf,
+        )
+        .unwrap_err();
+        assert_eq!(err.message, "Failed to unwrap private key");
+        assert_eq!(err.kind, ErrorKind::MacFailure);
+    }
+}
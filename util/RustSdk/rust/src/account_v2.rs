@@ -0,0 +1,282 @@
+//! Account encryption V2 key generation for the Seeder.
+//!
+//! A V2 account's user key is a COSE XChaCha20-Poly1305 key rather than AES-256-CBC-HMAC, and the
+//! account additionally holds an Ed25519 signing key pair, a public key signed with it, and a signed
+//! security state. All crypto runs through the same `bitwarden_crypto` primitives real clients use;
+//! only ciphertext and public material ever leave this module. The user and signing keys come from
+//! `rng`, so a seeded run reproduces them and everything derived from them; the type-7 wrappings of
+//! the private and signing keys keep random nonces.
+
+use std::ffi::{c_char, CStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use bitwarden_crypto::{
+    CoseSerializable, Kdf, KeyEncryptable, SignedPublicKeyMessage, SigningNamespace,
+};
+
+use crate::crypto_util::{
+    error_response, ffi_boundary, kdf_columns, make_kdf, success_response, SdkError,
+};
+use crate::master_key_cache::derive_master_key;
+use crate::{keypair, pool_public_key, rng};
+
+/// The security version a freshly enrolled V2 account starts at.
+const SECURITY_VERSION: u64 = 2;
+
+/// The client's security state: the account's security version, bound to the user ID and signed
+/// with the user's signing key so the server cannot roll it back. Field for field the SDK's
+/// `SecurityState`, which lives outside `bitwarden_crypto`; `entity_id` must stay a `Uuid`, which
+/// signs as a 16-byte CBOR byte string rather than the text of the ID.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecurityState {
+    entity_id: Uuid,
+    version: u64,
+}
+
+/// Generate the key material of an account encryption V2 user.
+///
+/// # Arguments
+/// * `email` - The user's email, used as the master key salt
+/// * `password` - The user's master password
+/// * `user_id` - The user's ID as a UUID, bound into the signed security state
+/// * `kdf_type` - 0 = PBKDF2-SHA256, 1 = Argon2id
+/// * `kdf_iterations` - KDF iterations; must be non-zero
+/// * `kdf_memory` - Argon2id memory in MiB; ignored for PBKDF2
+/// * `kdf_parallelism` - Argon2id parallelism; ignored for PBKDF2
+/// * `pool_index` - Index into the pre-generated RSA keypair pool
+///
+/// # Returns
+/// The `generate_user_keys_with_kdf` JSON, where `key` is a COSE XChaCha20-Poly1305 user key and
+/// `privateKey` is wrapped by it, plus `signingKey` (wrapped by the user key), `verifyingKey`,
+/// `signedPublicKey`, `securityState` and `securityVersion`.
+///
+/// # Safety
+/// `email`, `password` and `user_id` must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn generate_user_keys_v2(
+    email: *const c_char,
+    password: *const c_char,
+    user_id: *const c_char,
+    kdf_type: u32,
+    kdf_iterations: u32,
+    kdf_memory: u32,
+    kdf_parallelism: u32,
+    pool_index: u32,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(email) = CStr::from_ptr(email).to_str() else {
+            return error_response(SdkError::invalid_utf8("email"));
+        };
+        let Ok(password) = CStr::from_ptr(password).to_str() else {
+            return error_response(SdkError::invalid_utf8("password"));
+        };
+        let Ok(user_id) = CStr::from_ptr(user_id).to_str() else {
+            return error_response(SdkError::invalid_utf8("user_id"));
+        };
+
+        let kdf = match make_kdf(kdf_type, kdf_iterations, kdf_memory, kdf_parallelism) {
+            Ok(kdf) => kdf,
+            Err(err) => return error_response(err),
+        };
+
+        match generate_user_keys_v2_internal(email, password, user_id, &kdf, pool_index) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn generate_user_keys_v2_internal(
+    email: &str,
+    password: &str,
+    user_id: &str,
+    kdf: &Kdf,
+    pool_index: u32,
+) -> Result<String, SdkError> {
+    let user_id =
+        Uuid::parse_str(user_id).map_err(|_| SdkError::invalid_input("Invalid user ID"))?;
+
+    let derived = derive_master_key(email, password, kdf)?;
+    let master_password_hash = &derived.master_password_hash;
+
+    let user_key = rng::make_xchacha20_poly1305_key()?;
+    let encrypted_user_key = rng::wrap_user_key(&derived.master_key, &user_key)?;
+
+    let keypair = keypair(&user_key, pool_index)?;
+    let public_key = pool_public_key(pool_index);
+
+    let signing_key = rng::make_ed25519_signing_key()?;
+    let verifying_key = signing_key.to_verifying_key();
+    let wrapped_signing_key = signing_key
+        .to_cose()
+        .encrypt_with_key(&user_key)
+        .map_err(|_| SdkError::crypto_failure("Failed to wrap signing key"))?;
+
+    let signed_public_key = SignedPublicKeyMessage::from_public_key(&public_key)
+        .and_then(|message| message.sign(&signing_key))
+        .map_err(|_| SdkError::crypto_failure("Failed to sign public key"))?;
+
+    let security_state = SecurityState {
+        entity_id: user_id,
+        version: SECURITY_VERSION,
+    };
+    let signed_security_state = signing_key
+        .sign(&security_state, &SigningNamespace::SecurityState)
+        .map_err(|_| SdkError::crypto_failure("Failed to sign security state"))?;
+
+    let (kdf_type, kdf_iterations, kdf_memory, kdf_parallelism) = kdf_columns(kdf);
+
+    let result = serde_json::json!({
+        "masterPasswordHash": master_password_hash,
+        "key": user_key.to_base64(),
+        "encryptedUserKey": encrypted_user_key.to_string(),
+        "publicKey": keypair.public.to_string(),
+        "privateKey": keypair.private.to_string(),
+        "signingKey": wrapped_signing_key.to_string(),
+        "verifyingKey": STANDARD.encode(verifying_key.to_cose().as_ref()),
+        "signedPublicKey": String::from(signed_public_key),
+        "securityState": STANDARD.encode(signed_security_state.to_cose()),
+        "securityVersion": SECURITY_VERSION,
+        "kdf": kdf_type,
+        "kdfIterations": kdf_iterations,
+        "kdfMemory": kdf_memory,
+        "kdfParallelism": kdf_parallelism,
+    });
+
+    serde_json::to_string(&result)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize V2 user keys"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::{parse_key, ErrorKind, KDF_PBKDF2};
+    use bitwarden_crypto::{
+        CoseKeyBytes, CoseSign1Bytes, EncString, KeyDecryptable, MasterKey, SignedObject,
+        SignedPublicKey, SymmetricCryptoKey, VerifyingKey,
+    };
+
+    fn generate(user_id: &str) -> serde_json::Value {
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        let json =
+            generate_user_keys_v2_internal("v2@example.com", "asdfasdfasdf", user_id, &kdf, 3)
+                .unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn generate_user_keys_v2_produces_cose_user_key() {
+        let parsed = generate("00000000-0000-0000-0000-000000000001");
+
+        let user_key = parse_key(parsed["key"].as_str().unwrap()).unwrap();
+        assert!(
+            matches!(user_key, SymmetricCryptoKey::XChaCha20Poly1305Key(_)),
+            "V2 user keys must be XChaCha20-Poly1305"
+        );
+
+        // The private key and signing key are wrapped as COSE (type 7) EncStrings.
+        for field in ["privateKey", "signingKey"] {
+            let wrapped = parsed[field].as_str().unwrap();
+            assert!(
+                wrapped.starts_with("7."),
+                "{field} must be COSE, got: {wrapped}"
+            );
+            let enc: EncString = wrapped.parse().unwrap();
+            let decrypted: Result<Vec<u8>, _> = enc.decrypt_with_key(&user_key);
+            assert!(decrypted.is_ok(), "{field} must decrypt with the user key");
+        }
+    }
+
+    #[test]
+    fn generate_user_keys_v2_wraps_user_key_with_master_key() {
+        let parsed = generate("00000000-0000-0000-0000-000000000002");
+
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        let master_key = MasterKey::derive("asdfasdfasdf", "v2@example.com", &kdf).unwrap();
+        let encrypted: EncString = parsed["encryptedUserKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let user_key = master_key.decrypt_user_key(encrypted).unwrap();
+        assert_eq!(
+            <String>::from(user_key.to_base64()),
+            parsed["key"].as_str().unwrap()
+        );
+        assert_eq!(parsed["securityVersion"].as_u64().unwrap(), 2);
+    }
+
+    #[test]
+    fn generate_user_keys_v2_signs_with_the_returned_verifying_key() {
+        let user_id = "00000000-0000-0000-0000-000000000003";
+        let parsed = generate(user_id);
+
+        let verifying_key = VerifyingKey::from_cose(&CoseKeyBytes::from(
+            STANDARD
+                .decode(parsed["verifyingKey"].as_str().unwrap())
+                .unwrap(),
+        ))
+        .unwrap();
+
+        let signed_public_key =
+            SignedPublicKey::try_from(parsed["signedPublicKey"].as_str().unwrap().to_string())
+                .unwrap();
+        let public_key = signed_public_key.verify_and_unwrap(&verifying_key).unwrap();
+        assert_eq!(
+            STANDARD.encode(public_key.to_der().unwrap().as_ref()),
+            parsed["publicKey"].as_str().unwrap()
+        );
+
+        let signed_security_state = SignedObject::from_cose(&CoseSign1Bytes::from(
+            STANDARD
+                .decode(parsed["securityState"].as_str().unwrap())
+                .unwrap(),
+        ))
+        .unwrap();
+        let security_state: SecurityState = signed_security_state
+            .verify_and_unwrap(&verifying_key, &SigningNamespace::SecurityState)
+            .unwrap();
+        assert_eq!(security_state.entity_id, Uuid::parse_str(user_id).unwrap());
+        assert_eq!(security_state.version, SECURITY_VERSION);
+    }
+
+    #[test]
+    fn generate_user_keys_v2_is_reproducible_when_seeded() {
+        let user_id = "00000000-0000-0000-0000-000000000004";
+        let first = rng::with_test_seed(42, || generate(user_id));
+        let second = rng::with_test_seed(42, || generate(user_id));
+
+        for field in [
+            "key",
+            "encryptedUserKey",
+            "verifyingKey",
+            "signedPublicKey",
+            "securityState",
+        ] {
+            assert_eq!(first[field], second[field], "{field} must follow the seed");
+        }
+        assert_ne!(generate(user_id)["key"], first["key"]);
+
+        // The seeded user key is still wrapped the way clients unwrap it.
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        let master_key = MasterKey::derive("asdfasdfasdf", "v2@example.com", &kdf).unwrap();
+        let encrypted: EncString = first["encryptedUserKey"].as_str().unwrap().parse().unwrap();
+        assert_eq!(
+            <String>::from(master_key.decrypt_user_key(encrypted).unwrap().to_base64()),
+            first["key"].as_str().unwrap()
+        );
+    }
+
+    #[test]
+    fn generate_user_keys_v2_rejects_non_uuid_user_id() {
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        let err =
+            generate_user_keys_v2_internal("v2@example.com", "asdfasdfasdf", "not-a-uuid", &kdf, 3)
+                .unwrap_err();
+        assert_eq!(err.kind, ErrorKind::InvalidInput);
+    }
+}
@@ -1,160 +1,385 @@
 //! Attachment encryption for the Seeder.
 //!
 //! Encrypts an attachment's file bytes and filename in one of Bitwarden's canonical attachment scheme
-//! versions (v0/v1/v2) so clients exercise every attachment decrypt branch. All crypto runs through the
+//! versions (v0/v1/v2) so clients exercise every attachment decrypt branch, and decrypts them back with
+//! scheme detection so tests can verify what was written to storage. All crypto runs through the
 //! same `bitwarden_crypto` primitives real clients use; only ciphertext ever leaves this module.
 
-use std::ffi::{c_char, CStr, CString};
+use std::ffi::{c_char, CStr};
 
-use base64::{engine::general_purpose::STANDARD, Engine};
+use bitwarden_crypto::{EncString, KeyDecryptable, OctetStreamBytes, SymmetricCryptoKey};
 
-use bitwarden_crypto::{
-    EncString, KeyEncryptable, OctetStreamBytes, SymmetricCryptoKey, SymmetricKeyAlgorithm,
+use crate::crypto_util::{
+    byte_slice, error_response, ffi_boundary, parse_key, success_response, unwrap_key, wrap_key,
+    ErrorKind, SdkError,
 };
+use crate::key_handle::resolve_key;
+use crate::rng;
+use crate::ByteBuffer;
+
+/// Encrypt an attachment's bytes and filename in scheme version `mode`, returning
+/// `(EncArrayBuffer blob, encrypted filename, wrapped attachment key)`.
+pub(crate) fn encrypt_attachment_bytes(
+    file_bytes: &[u8],
+    vault_key: &SymmetricCryptoKey,
+    wrapped_cipher_key: &str,
+    filename: &str,
+    mode: u32,
+) -> Result<(Vec<u8>, String, Option<String>), SdkError> {
+    let (data_key, file_name, key) =
+        attachment_keys(vault_key, wrapped_cipher_key, filename, mode)?;
+    Ok((encrypt_buffer(file_bytes, &data_key)?, file_name, key))
+}
 
-use crate::crypto_util::{error_response, parse_key, unwrap_key, wrap_key};
+/// Resolve the key that encrypts an attachment's bytes in scheme version `mode`, returning
+/// `(data key, encrypted filename, wrapped attachment key)`. Buffered and streaming encryption both
+/// start here, so a seeded run draws the same keys and IVs in the same order either way.
+pub(crate) fn attachment_keys(
+    vault_key: &SymmetricCryptoKey,
+    wrapped_cipher_key: &str,
+    filename: &str,
+    mode: u32,
+) -> Result<(SymmetricCryptoKey, String, Option<String>), SdkError> {
+    Ok(match mode {
+        // v0 (account-key-based): no attachment key. Bytes and filename encrypted directly with the vault key.
+        0 => (vault_key.clone(), encrypt_str(filename, vault_key)?, None),
+        // v1 (attachment-key-based). Bytes with the attachment key; filename with the vault key.
+        1 => {
+            let attachment_key = rng::make_symmetric_key();
+            let wrapped = wrap_key(&attachment_key, vault_key)?;
+            (
+                attachment_key,
+                encrypt_str(filename, vault_key)?,
+                Some(wrapped),
+            )
+        }
+        // v2 (attachment-cipher-key-based). Bytes with the attachment key; filename with the cipher key.
+        2 => {
+            if wrapped_cipher_key.trim().is_empty() {
+                return Err(SdkError::invalid_input(
+                    "Attachment v2 requires a wrapped_cipher_key",
+                ));
+            }
+            let cipher_key = unwrap_key(wrapped_cipher_key, vault_key)?;
+            let attachment_key = rng::make_symmetric_key();
+            let wrapped = wrap_key(&attachment_key, &cipher_key)?;
+            (
+                attachment_key,
+                encrypt_str(filename, &cipher_key)?,
+                Some(wrapped),
+            )
+        }
+        _ => {
+            return Err(SdkError::unsupported_mode(format!(
+                "Unsupported attachment scheme version: {mode}"
+            )))
+        }
+    })
+}
 
 /// Encrypt an attachment's file bytes and filename for the Seeder in one of Bitwarden's attachment
-/// scheme versions (v0/v1/v2) so clients exercise every decrypt branch.
+/// scheme versions (v0/v1/v2) so clients exercise every decrypt branch. The file crosses the boundary
+/// as a raw byte buffer and the EncArrayBuffer blob (`0x02 | iv | mac | ciphertext`) is written to
+/// `blob_out`, so files of any size and content, NUL bytes included, need no encoding.
 ///
 /// # Arguments
-/// * `file_bytes_b64` - Base64-encoded plaintext file bytes
+/// * `file_bytes` / `file_len` - The plaintext file bytes
 /// * `vault_key_b64` - Base64-encoded vault key (the user or organization symmetric key)
 /// * `wrapped_cipher_key` - The cipher's wrapped `Key` EncString; only used for v2. Pass "" for none.
 /// * `filename` - The plaintext filename
 /// * `mode` - 0 = v0 no attachment key (bytes+filename with vault key); 1 = v1 attachment key wrapped by
 ///   vault key; 2 = v2 attachment key wrapped by the cipher key (unwrapped from `wrapped_cipher_key`)
+/// * `blob_out` - Receives the blob on success and an empty buffer on failure; release it with
+///   `free_byte_buffer`
 ///
 /// # Returns
-/// JSON `{ "key": <EncString|null>, "fileName": <EncString>, "blob": <base64 EncArrayBuffer>, "size": <u64> }`.
-/// The blob is the AES-256-CBC-HMAC EncArrayBuffer binary layout (`0x02 | iv | mac | ciphertext`).
+/// JSON `{ "key": <EncString|null>, "fileName": <EncString>, "size": <u64> }`
 ///
 /// # Safety
-/// All pointers must be valid null-terminated strings.
+/// `file_bytes` must point to `file_len` readable bytes (or be null when empty), the string pointers
+/// must be valid null-terminated strings, and `blob_out` must be valid for writes.
 #[no_mangle]
-pub unsafe extern "C" fn encrypt_attachment(
-    file_bytes_b64: *const c_char,
+pub unsafe extern "C" fn encrypt_attachment_buffer(
+    file_bytes: *const u8,
+    file_len: usize,
     vault_key_b64: *const c_char,
     wrapped_cipher_key: *const c_char,
     filename: *const c_char,
     mode: u32,
+    blob_out: *mut ByteBuffer,
 ) -> *const c_char {
-    let Ok(file_b64) = CStr::from_ptr(file_bytes_b64).to_str() else {
-        return error_response("Invalid UTF-8 in file_bytes_b64");
-    };
-    let Ok(vault_key_b64) = CStr::from_ptr(vault_key_b64).to_str() else {
-        return error_response("Invalid UTF-8 in vault_key_b64");
-    };
-    let Ok(wrapped_cipher_key) = CStr::from_ptr(wrapped_cipher_key).to_str() else {
-        return error_response("Invalid UTF-8 in wrapped_cipher_key");
-    };
-    let Ok(filename) = CStr::from_ptr(filename).to_str() else {
-        return error_response("Invalid UTF-8 in filename");
-    };
+    ffi_boundary(|| {
+        if blob_out.is_null() {
+            return error_response(SdkError::invalid_input("Invalid blob_out: null pointer"));
+        }
+        *blob_out = ByteBuffer::empty();
+
+        let Ok(vault_key_b64) = CStr::from_ptr(vault_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("vault_key_b64"));
+        };
+        let Ok(wrapped_cipher_key) = CStr::from_ptr(wrapped_cipher_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("wrapped_cipher_key"));
+        };
+        let Ok(filename) = CStr::from_ptr(filename).to_str() else {
+            return error_response(SdkError::invalid_utf8("filename"));
+        };
+
+        let file_bytes = byte_slice(file_bytes, file_len);
+        let result = parse_key(vault_key_b64).and_then(|vault_key| {
+            encrypt_attachment_bytes(file_bytes, &vault_key, wrapped_cipher_key, filename, mode)
+        });
+        encrypted_attachment_response(result, blob_out)
+    })
+}
 
-    match encrypt_attachment_internal(file_b64, vault_key_b64, wrapped_cipher_key, filename, mode) {
-        Ok(json) => CString::new(json).unwrap().into_raw(),
-        Err(msg) => error_response(&msg),
+/// Encrypt an attachment with a registered vault key: `encrypt_attachment_buffer` by handle.
+///
+/// # Arguments
+/// * `file_bytes` / `file_len` - The plaintext file bytes
+/// * `vault_key_handle` - Handle from `register_key` for the vault key
+/// * `wrapped_cipher_key` - The cipher's wrapped `Key` EncString; only used for v2. Pass "" for none.
+/// * `filename` - The plaintext filename
+/// * `mode` - Attachment scheme version, as for `encrypt_attachment_buffer`
+/// * `blob_out` - Receives the blob on success and an empty buffer on failure; release it with
+///   `free_byte_buffer`
+///
+/// # Returns
+/// The `encrypt_attachment_buffer` JSON.
+///
+/// # Safety
+/// `file_bytes` must point to `file_len` readable bytes (or be null when empty), the string pointers
+/// must be valid null-terminated strings, and `blob_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn encrypt_attachment_with_handle(
+    file_bytes: *const u8,
+    file_len: usize,
+    vault_key_handle: u64,
+    wrapped_cipher_key: *const c_char,
+    filename: *const c_char,
+    mode: u32,
+    blob_out: *mut ByteBuffer,
+) -> *const c_char {
+    ffi_boundary(|| {
+        if blob_out.is_null() {
+            return error_response(SdkError::invalid_input("Invalid blob_out: null pointer"));
+        }
+        *blob_out = ByteBuffer::empty();
+
+        let Ok(wrapped_cipher_key) = CStr::from_ptr(wrapped_cipher_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("wrapped_cipher_key"));
+        };
+        let Ok(filename) = CStr::from_ptr(filename).to_str() else {
+            return error_response(SdkError::invalid_utf8("filename"));
+        };
+
+        let file_bytes = byte_slice(file_bytes, file_len);
+        let result = resolve_key(vault_key_handle).and_then(|vault_key| {
+            encrypt_attachment_bytes(file_bytes, &vault_key, wrapped_cipher_key, filename, mode)
+        });
+        encrypted_attachment_response(result, blob_out)
+    })
+}
+
+/// Hand an encrypted attachment's blob to the caller through `blob_out` and describe it in the JSON
+/// response.
+///
+/// # Safety
+/// `blob_out` must be valid for writes.
+unsafe fn encrypted_attachment_response(
+    result: Result<(Vec<u8>, String, Option<String>), SdkError>,
+    blob_out: *mut ByteBuffer,
+) -> *const c_char {
+    match result {
+        Ok((blob, enc_filename, wrapped_key)) => {
+            let json = serde_json::json!({
+                "key": wrapped_key,
+                "fileName": enc_filename,
+                "size": blob.len() as u64,
+            })
+            .to_string();
+            *blob_out = ByteBuffer::from_vec(blob);
+            success_response(&json)
+        }
+        Err(err) => error_response(err),
     }
 }
 
-fn encrypt_attachment_internal(
-    file_b64: &str,
+/// Decrypt an attachment blob and filename, returning `(scheme, filename, plaintext bytes)`.
+fn decrypt_attachment_bytes(
+    blob: &[u8],
+    attachment_key: &str,
+    encrypted_filename: &str,
     vault_key_b64: &str,
     wrapped_cipher_key: &str,
-    filename: &str,
-    mode: u32,
-) -> Result<String, String> {
-    let file_bytes = STANDARD
-        .decode(file_b64)
-        .map_err(|_| "Failed to decode base64 file bytes".to_string())?;
+) -> Result<(u32, String, Vec<u8>), SdkError> {
     let vault_key = parse_key(vault_key_b64)?;
+    let cipher_key = match wrapped_cipher_key.trim() {
+        "" => None,
+        wrapped => Some(unwrap_key(wrapped, &vault_key)?),
+    };
 
-    let (blob, enc_filename, wrapped_key): (Vec<u8>, String, Option<String>) = match mode {
-        // v0 (account-key-based): no attachment key. Bytes and filename encrypted directly with the vault key.
-        0 => (
-            encrypt_buffer(&file_bytes, &vault_key)?,
-            encrypt_str(filename, &vault_key)?,
-            None,
-        ),
-        // v1 (attachment-key-based). Bytes with the attachment key; filename with the vault key.
-        1 => {
-            let attachment_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
-            (
-                encrypt_buffer(&file_bytes, &attachment_key)?,
-                encrypt_str(filename, &vault_key)?,
-                Some(wrap_key(&attachment_key, &vault_key)?),
-            )
+    let (scheme, data_key, filename_key) =
+        detect_attachment_keys(attachment_key, vault_key, cipher_key)?;
+
+    let data = decrypt_buffer(blob, &data_key)?;
+    let filename = decrypt_str(encrypted_filename, &filename_key)?;
+    Ok((scheme, filename, data))
+}
+
+/// Decrypt an attachment written by `encrypt_attachment_buffer` (or a real client), detecting which
+/// scheme version produced it, and write the plaintext bytes to `data_out`.
+///
+/// Detection follows how clients decrypt; see `detect_attachment_keys`.
+///
+/// # Arguments
+/// * `blob` / `blob_len` - The EncArrayBuffer blob, as stored
+/// * `attachment_key` - The attachment's wrapped `key` EncString. Pass "" for none.
+/// * `encrypted_filename` - The attachment's encrypted `fileName` EncString
+/// * `vault_key_b64` - Base64-encoded vault key (the user or organization symmetric key)
+/// * `wrapped_cipher_key` - The cipher's wrapped `Key` EncString. Pass "" for none.
+/// * `data_out` - Receives the plaintext on success and an empty buffer on failure; release it with
+///   `free_byte_buffer`
+///
+/// # Returns
+/// JSON `{ "scheme": <0|1|2>, "fileName": <string>, "size": <u64> }`
+///
+/// # Safety
+/// `blob` must point to `blob_len` readable bytes (or be null when empty), the string pointers must be
+/// valid null-terminated strings, and `data_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn decrypt_attachment_buffer(
+    blob: *const u8,
+    blob_len: usize,
+    attachment_key: *const c_char,
+    encrypted_filename: *const c_char,
+    vault_key_b64: *const c_char,
+    wrapped_cipher_key: *const c_char,
+    data_out: *mut ByteBuffer,
+) -> *const c_char {
+    ffi_boundary(|| {
+        if data_out.is_null() {
+            return error_response(SdkError::invalid_input("Invalid data_out: null pointer"));
         }
-        // v2 (attachment-cipher-key-based). Bytes with the attachment key; filename with the cipher key.
-        2 => {
-            if wrapped_cipher_key.trim().is_empty() {
-                return Err("Attachment v2 requires a wrapped_cipher_key".to_string());
+        *data_out = ByteBuffer::empty();
+
+        let Ok(attachment_key) = CStr::from_ptr(attachment_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("attachment_key"));
+        };
+        let Ok(encrypted_filename) = CStr::from_ptr(encrypted_filename).to_str() else {
+            return error_response(SdkError::invalid_utf8("encrypted_filename"));
+        };
+        let Ok(vault_key_b64) = CStr::from_ptr(vault_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("vault_key_b64"));
+        };
+        let Ok(wrapped_cipher_key) = CStr::from_ptr(wrapped_cipher_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("wrapped_cipher_key"));
+        };
+
+        match decrypt_attachment_bytes(
+            byte_slice(blob, blob_len),
+            attachment_key,
+            encrypted_filename,
+            vault_key_b64,
+            wrapped_cipher_key,
+        ) {
+            Ok((scheme, filename, data)) => {
+                let json = serde_json::json!({
+                    "scheme": scheme,
+                    "fileName": filename,
+                    "size": data.len() as u64,
+                })
+                .to_string();
+                *data_out = ByteBuffer::from_vec(data);
+                success_response(&json)
             }
-            let cipher_key = unwrap_key(wrapped_cipher_key, &vault_key)?;
-            let attachment_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
-            (
-                encrypt_buffer(&file_bytes, &attachment_key)?,
-                encrypt_str(filename, &cipher_key)?,
-                Some(wrap_key(&attachment_key, &cipher_key)?),
-            )
+            Err(err) => error_response(err),
         }
-        _ => return Err(format!("Unsupported attachment scheme version: {mode}")),
-    };
-
-    let result = serde_json::json!({
-        "key": wrapped_key,
-        "fileName": enc_filename,
-        "blob": STANDARD.encode(&blob),
-        "size": blob.len() as u64,
-    });
+    })
+}
 
-    serde_json::to_string(&result).map_err(|_| "Failed to serialize attachment result".to_string())
+/// Work out which scheme version an attachment was written with and the keys it needs.
+///
+/// Returns `(scheme, key that decrypts the bytes, key that decrypts the filename)`. No attachment key
+/// means v0; an attachment key that unwraps with the cipher key means v2; otherwise it must unwrap
+/// with the vault key (v1).
+pub(crate) fn detect_attachment_keys(
+    attachment_key: &str,
+    vault_key: SymmetricCryptoKey,
+    cipher_key: Option<SymmetricCryptoKey>,
+) -> Result<(u32, SymmetricCryptoKey, SymmetricCryptoKey), SdkError> {
+    Ok(match attachment_key.trim() {
+        "" => (0, vault_key.clone(), vault_key),
+        wrapped => {
+            let v2_key = cipher_key
+                .as_ref()
+                .and_then(|cipher_key| unwrap_key(wrapped, cipher_key).ok());
+            match (v2_key, cipher_key) {
+                (Some(attachment_key), Some(cipher_key)) => (2, attachment_key, cipher_key),
+                _ => (1, unwrap_key(wrapped, &vault_key)?, vault_key),
+            }
+        }
+    })
 }
 
 /// Encrypt a raw byte buffer with a symmetric key and serialize it to the EncArrayBuffer binary layout.
-fn encrypt_buffer(bytes: &[u8], key: &SymmetricCryptoKey) -> Result<Vec<u8>, String> {
-    let encrypted: EncString = OctetStreamBytes::from(bytes.to_vec())
-        .encrypt_with_key(key)
-        .map_err(|_| "Failed to encrypt attachment data".to_string())?;
+pub(crate) fn encrypt_buffer(bytes: &[u8], key: &SymmetricCryptoKey) -> Result<Vec<u8>, SdkError> {
+    let encrypted = rng::encrypt(&OctetStreamBytes::from(bytes.to_vec()), key)
+        .map_err(|_| SdkError::crypto_failure("Failed to encrypt attachment data"))?;
     encrypted
         .to_buffer()
-        .map_err(|_| "Failed to serialize attachment buffer".to_string())
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize attachment buffer"))
 }
 
 /// Encrypt a plaintext string with a symmetric key, returning an EncString.
-fn encrypt_str(plaintext: &str, key: &SymmetricCryptoKey) -> Result<String, String> {
-    let encrypted = plaintext
-        .to_string()
-        .encrypt_with_key(key)
-        .map_err(|_| "Failed to encrypt string".to_string())?;
+pub(crate) fn encrypt_str(plaintext: &str, key: &SymmetricCryptoKey) -> Result<String, SdkError> {
+    let encrypted = rng::encrypt(plaintext.to_string(), key)
+        .map_err(|_| SdkError::crypto_failure("Failed to encrypt string"))?;
     Ok(encrypted.to_string())
 }
 
+/// Parse an EncArrayBuffer blob and decrypt it with a symmetric key.
+pub(crate) fn decrypt_buffer(blob: &[u8], key: &SymmetricCryptoKey) -> Result<Vec<u8>, SdkError> {
+    let encrypted = EncString::from_buffer(blob)
+        .map_err(|_| SdkError::invalid_input("Failed to parse attachment buffer"))?;
+    encrypted
+        .decrypt_with_key(key)
+        .map_err(SdkError::from_crypto(
+            ErrorKind::DecryptionFailed,
+            "Failed to decrypt attachment data",
+        ))
+}
+
+/// Decrypt an EncString with a symmetric key, returning the plaintext.
+pub(crate) fn decrypt_str(enc_string: &str, key: &SymmetricCryptoKey) -> Result<String, SdkError> {
+    let encrypted: EncString = enc_string
+        .parse()
+        .map_err(|_| SdkError::invalid_input("Failed to parse EncString"))?;
+    encrypted
+        .decrypt_with_key(key)
+        .map_err(SdkError::from_crypto(
+            ErrorKind::DecryptionFailed,
+            "Failed to decrypt string",
+        ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bitwarden_crypto::KeyDecryptable;
-
-    fn make_test_key() -> SymmetricCryptoKey {
-        SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac)
-    }
+    use crate::crypto_util::{make_test_key, take_response, ErrorKind};
+    use std::ffi::CString;
 
     #[test]
     fn encrypt_attachment_v0_no_key_roundtrip() {
         let vault = make_test_key();
-        let vault_b64: String = vault.to_base64().into();
         let data = b"hello attachment";
-        let data_b64 = STANDARD.encode(data);
 
-        let json = encrypt_attachment_internal(&data_b64, &vault_b64, "", "notes.txt", 0).unwrap();
-        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let (blob, file_name, key) =
+            encrypt_attachment_bytes(data, &vault, "", "notes.txt", 0).unwrap();
 
         // v0 carries no attachment key.
-        assert!(parsed["key"].is_null(), "v0 must have a null key");
+        assert!(key.is_none(), "v0 must have a null key");
 
         // Blob is a type-2 EncArrayBuffer and decrypts directly with the vault key.
-        let blob = STANDARD.decode(parsed["blob"].as_str().unwrap()).unwrap();
         assert_eq!(
             blob[0], 2,
             "blob must be an AES-256-CBC-HMAC EncArrayBuffer"
@@ -164,40 +389,34 @@ mod tests {
         assert_eq!(decrypted, data);
 
         // Filename decrypts with the vault key.
-        let fname: EncString = parsed["fileName"].as_str().unwrap().parse().unwrap();
+        let fname: EncString = file_name.parse().unwrap();
         let fname_dec: String = fname.decrypt_with_key(&vault).unwrap();
         assert_eq!(fname_dec, "notes.txt");
-
-        // Size equals the encrypted blob length.
-        assert_eq!(parsed["size"].as_u64().unwrap(), blob.len() as u64);
     }
 
     #[test]
     fn encrypt_attachment_v1_vault_wrapped_roundtrip() {
         let vault = make_test_key();
-        let vault_b64: String = vault.to_base64().into();
         let data = b"pdf-ish bytes";
-        let data_b64 = STANDARD.encode(data);
 
-        let json = encrypt_attachment_internal(&data_b64, &vault_b64, "", "report.pdf", 1).unwrap();
-        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let (blob, file_name, key) =
+            encrypt_attachment_bytes(data, &vault, "", "report.pdf", 1).unwrap();
 
         // Attachment key present, wrapped by the vault key.
-        let wrapped = parsed["key"].as_str().unwrap();
+        let wrapped = key.unwrap();
         assert!(
             wrapped.starts_with("2."),
             "wrapped key must be a type-2 EncString"
         );
-        let attachment_key = unwrap_key(wrapped, &vault).unwrap();
+        let attachment_key = unwrap_key(&wrapped, &vault).unwrap();
 
         // Blob decrypts with the unwrapped attachment key.
-        let blob = STANDARD.decode(parsed["blob"].as_str().unwrap()).unwrap();
         let enc = EncString::from_buffer(&blob).unwrap();
         let decrypted: Vec<u8> = enc.decrypt_with_key(&attachment_key).unwrap();
         assert_eq!(decrypted, data);
 
         // Filename decrypts with the vault key (never the attachment key).
-        let fname: EncString = parsed["fileName"].as_str().unwrap().parse().unwrap();
+        let fname: EncString = file_name.parse().unwrap();
         let fname_dec: String = fname.decrypt_with_key(&vault).unwrap();
         assert_eq!(fname_dec, "report.pdf");
     }
@@ -205,35 +424,30 @@ mod tests {
     #[test]
     fn encrypt_attachment_v2_cipher_wrapped_roundtrip() {
         let vault = make_test_key();
-        let vault_b64: String = vault.to_base64().into();
 
         // Simulate a cipher-key cipher: a cipher key wrapped by the vault key.
         let cipher_key = make_test_key();
         let wrapped_cipher_key = wrap_key(&cipher_key, &vault).unwrap();
 
         let data = b"secret-5";
-        let data_b64 = STANDARD.encode(data);
 
-        let json =
-            encrypt_attachment_internal(&data_b64, &vault_b64, &wrapped_cipher_key, "m5.bin", 2)
-                .unwrap();
-        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let (blob, file_name, key) =
+            encrypt_attachment_bytes(data, &vault, &wrapped_cipher_key, "m5.bin", 2).unwrap();
 
         // Attachment key unwraps with the cipher key, not the vault key.
-        let wrapped = parsed["key"].as_str().unwrap();
-        let attachment_key = unwrap_key(wrapped, &cipher_key).unwrap();
+        let wrapped = key.unwrap();
+        let attachment_key = unwrap_key(&wrapped, &cipher_key).unwrap();
         assert!(
-            unwrap_key(wrapped, &vault).is_err(),
+            unwrap_key(&wrapped, &vault).is_err(),
             "must not unwrap with the vault key"
         );
 
-        let blob = STANDARD.decode(parsed["blob"].as_str().unwrap()).unwrap();
         let enc = EncString::from_buffer(&blob).unwrap();
         let decrypted: Vec<u8> = enc.decrypt_with_key(&attachment_key).unwrap();
         assert_eq!(decrypted, data);
 
         // Filename decrypts with the cipher key.
-        let fname: EncString = parsed["fileName"].as_str().unwrap().parse().unwrap();
+        let fname: EncString = file_name.parse().unwrap();
         let fname_dec: String = fname.decrypt_with_key(&cipher_key).unwrap();
         assert_eq!(fname_dec, "m5.bin");
     }
@@ -241,36 +455,167 @@ mod tests {
     #[test]
     fn encrypt_attachment_v2_requires_cipher_key() {
         let vault = make_test_key();
-        let vault_b64: String = vault.to_base64().into();
-        let data_b64 = STANDARD.encode(b"x");
 
-        let err = encrypt_attachment_internal(&data_b64, &vault_b64, "", "x.txt", 2).unwrap_err();
-        assert!(err.contains("v2"), "got: {err}");
+        let err = encrypt_attachment_bytes(b"x", &vault, "", "x.txt", 2).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::InvalidInput);
+        assert!(err.message.contains("v2"), "got: {err}");
     }
 
     #[test]
     fn encrypt_attachment_rejects_unsupported_mode() {
         let vault = make_test_key();
-        let vault_b64: String = vault.to_base64().into();
-        let data_b64 = STANDARD.encode(b"x");
 
-        let err = encrypt_attachment_internal(&data_b64, &vault_b64, "", "x.txt", 3).unwrap_err();
-        assert!(
-            err.contains("Unsupported attachment scheme version"),
-            "got: {err}"
-        );
+        let err = encrypt_attachment_bytes(b"x", &vault, "", "x.txt", 3).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::UnsupportedMode, "got: {err}");
     }
 
     #[test]
-    fn encrypt_attachment_rejects_invalid_base64_file() {
+    fn decrypt_attachment_detects_every_scheme() {
         let vault = make_test_key();
         let vault_b64: String = vault.to_base64().into();
+        let wrapped_cipher_key = wrap_key(&make_test_key(), &vault).unwrap();
+        let data = b"attachment bytes";
+
+        for (mode, cipher_key) in [
+            (0, ""),
+            (1, ""),
+            (1, wrapped_cipher_key.as_str()),
+            (2, wrapped_cipher_key.as_str()),
+        ] {
+            let (blob, file_name, key) =
+                encrypt_attachment_bytes(data, &vault, cipher_key, "a.bin", mode).unwrap();
+
+            let (scheme, decrypted_name, decrypted) = decrypt_attachment_bytes(
+                &blob,
+                key.as_deref().unwrap_or(""),
+                &file_name,
+                &vault_b64,
+                cipher_key,
+            )
+            .unwrap();
 
-        let err = encrypt_attachment_internal("not*valid*base64", &vault_b64, "", "x.txt", 0)
-            .unwrap_err();
-        assert!(
-            err.contains("Failed to decode base64 file bytes"),
-            "got: {err}"
+            assert_eq!(scheme, mode);
+            assert_eq!(decrypted_name, "a.bin");
+            assert_eq!(decrypted, data);
+        }
+    }
+
+    #[test]
+    fn decrypt_attachment_rejects_wrong_vault_key() {
+        let vault = make_test_key();
+        let other_b64: String = make_test_key().to_base64().into();
+        let (blob, file_name, key) =
+            encrypt_attachment_bytes(b"x", &vault, "", "x.txt", 1).unwrap();
+
+        let err =
+            decrypt_attachment_bytes(&blob, &key.unwrap(), &file_name, &other_b64, "").unwrap_err();
+        assert_eq!(err.message, "Failed to unwrap key");
+        assert_eq!(err.kind, ErrorKind::MacFailure);
+    }
+
+    #[test]
+    fn encrypt_attachment_with_handle_writes_the_blob() {
+        let vault = make_test_key();
+        let vault_b64 = CString::new(<String>::from(vault.to_base64())).unwrap();
+        let handle: u64 =
+            take_response(unsafe { crate::key_handle::register_key(vault_b64.as_ptr()) })
+                .unwrap()
+                .parse()
+                .unwrap();
+        let empty = CString::new("").unwrap();
+        let filename = CString::new("h.bin").unwrap();
+        let data = b"by handle";
+
+        let mut blob = ByteBuffer::empty();
+        let ptr = unsafe {
+            encrypt_attachment_with_handle(
+                data.as_ptr(),
+                data.len(),
+                handle,
+                empty.as_ptr(),
+                filename.as_ptr(),
+                0,
+                &mut blob,
+            )
+        };
+        let encrypted: serde_json::Value =
+            serde_json::from_str(&take_response(ptr).unwrap()).unwrap();
+        let blob_bytes = unsafe { byte_slice(blob.data, blob.len) }.to_vec();
+        unsafe {
+            crate::free_byte_buffer(blob);
+            crate::key_handle::release_key(handle);
+        }
+
+        assert_eq!(encrypted["size"].as_u64().unwrap(), blob_bytes.len() as u64);
+        assert_eq!(decrypt_buffer(&blob_bytes, &vault).unwrap(), data);
+
+        let mut blob = ByteBuffer::empty();
+        let ptr = unsafe {
+            encrypt_attachment_with_handle(
+                data.as_ptr(),
+                data.len(),
+                handle,
+                empty.as_ptr(),
+                filename.as_ptr(),
+                0,
+                &mut blob,
+            )
+        };
+        assert_eq!(take_response(ptr), Err("invalidInput".to_string()));
+        assert_eq!(blob.len, 0, "a failed call leaves an empty buffer");
+    }
+
+    #[test]
+    fn attachment_buffers_roundtrip_binary_content() {
+        let vault_b64 = CString::new(<String>::from(make_test_key().to_base64())).unwrap();
+        let empty = CString::new("").unwrap();
+        let filename = CString::new("dump.bin").unwrap();
+        let data: Vec<u8> = (0..=255).chain([0, 0, 0]).collect();
+
+        let mut blob = ByteBuffer::empty();
+        let ptr = unsafe {
+            encrypt_attachment_buffer(
+                data.as_ptr(),
+                data.len(),
+                vault_b64.as_ptr(),
+                empty.as_ptr(),
+                filename.as_ptr(),
+                1,
+                &mut blob,
+            )
+        };
+        let encrypted: serde_json::Value =
+            serde_json::from_str(&take_response(ptr).unwrap()).unwrap();
+        assert_eq!(encrypted["size"].as_u64().unwrap(), blob.len as u64);
+        assert_eq!(
+            unsafe { *blob.data },
+            2,
+            "EncArrayBuffer starts with the type byte"
         );
+
+        let key = CString::new(encrypted["key"].as_str().unwrap()).unwrap();
+        let encrypted_filename = CString::new(encrypted["fileName"].as_str().unwrap()).unwrap();
+        let mut plaintext = ByteBuffer::empty();
+        let ptr = unsafe {
+            decrypt_attachment_buffer(
+                blob.data,
+                blob.len,
+                key.as_ptr(),
+                encrypted_filename.as_ptr(),
+                vault_b64.as_ptr(),
+                empty.as_ptr(),
+                &mut plaintext,
+            )
+        };
+        let decrypted: serde_json::Value =
+            serde_json::from_str(&take_response(ptr).unwrap()).unwrap();
+        assert_eq!(decrypted["scheme"].as_u64().unwrap(), 1);
+        assert_eq!(decrypted["fileName"].as_str().unwrap(), "dump.bin");
+        assert_eq!(unsafe { byte_slice(plaintext.data, plaintext.len) }, data);
+
+        unsafe {
+            crate::free_byte_buffer(blob);
+            crate::free_byte_buffer(plaintext);
+        }
     }
 }
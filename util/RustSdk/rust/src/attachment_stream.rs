@@ -0,0 +1,559 @@
+//! Streaming attachment encryption for the Seeder.
+//!
+//! `encrypt_attachment_buffer` holds the whole file and its ciphertext in memory at once, which rules
+//! out attachments near the 500 MB limit. A stream instead encrypts chunk by chunk straight into a file
+//! or a caller-supplied write callback, producing the same EncArrayBuffer layout
+//! (`0x02 | iv | mac | ciphertext`). The MAC covers the whole ciphertext, so its slot is reserved when
+//! the stream starts and filled in by `attachment_stream_finalize`: sinks must accept a write at an
+//! earlier offset.
+//!
+//! Streams are addressed by handles, like registered keys. A stream that fails to feed or finalize is
+//! dropped; `attachment_stream_abort` drops one explicitly. A stream is taken out of the handle table
+//! while it is fed, so feeds of different streams run in parallel, and a call on a stream that is
+//! mid-feed (e.g. from its own write callback) fails with an invalid handle rather than blocking.
+
+use std::{
+    collections::HashMap,
+    ffi::{c_char, c_void, CStr},
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        LazyLock, Mutex, PoisonError,
+    },
+};
+
+use aes::Aes256;
+use bitwarden_crypto::SymmetricCryptoKey;
+use cbc::cipher::{BlockModeEncrypt, KeyIvInit};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+
+use crate::attachment::attachment_keys;
+use crate::crypto_util::{
+    byte_slice, error_response, ffi_boundary, parse_key, success_response, SdkError,
+};
+use crate::rng;
+
+const BLOCK_LEN: usize = 16;
+/// `0x02 | iv | mac`, ahead of the ciphertext.
+const HEADER_LEN: u64 = 1 + 16 + 32;
+const MAC_OFFSET: u64 = 1 + 16;
+
+/// `write(context, offset, data, len)` stores `len` bytes at `offset` of the blob and returns 0, or
+/// returns non-zero to fail the stream.
+type WriteCallback = extern "C" fn(*mut c_void, u64, *const u8, usize) -> i32;
+
+static STREAMS: LazyLock<Mutex<HashMap<u64, AttachmentStream>>> = LazyLock::new(Default::default);
+
+/// Handle 0 is never issued.
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+enum Sink {
+    File {
+        writer: BufWriter<File>,
+        position: u64,
+    },
+    Callback {
+        write: WriteCallback,
+        context: *mut c_void,
+    },
+}
+
+// SAFETY: the callback context is only dereferenced by the caller's own callback, during calls the
+// caller makes with this stream's handle.
+unsafe impl Send for Sink {}
+
+impl Sink {
+    fn write_at(&mut self, offset: u64, bytes: &[u8]) -> Result<(), SdkError> {
+        match self {
+            Sink::File { writer, position } => {
+                if *position != offset {
+                    writer.seek(SeekFrom::Start(offset)).map_err(|e| {
+                        SdkError::io(format!("Failed to seek attachment file: {e}"))
+                    })?;
+                }
+                writer
+                    .write_all(bytes)
+                    .map_err(|e| SdkError::io(format!("Failed to write attachment file: {e}")))?;
+                *position = offset + bytes.len() as u64;
+                Ok(())
+            }
+            Sink::Callback { write, context } => {
+                match write(*context, offset, bytes.as_ptr(), bytes.len()) {
+                    0 => Ok(()),
+                    status => Err(SdkError::io(format!(
+                        "Attachment write callback failed with {status}"
+                    ))),
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), SdkError> {
+        match self {
+            Sink::File { writer, .. } => writer
+                .flush()
+                .map_err(|e| SdkError::io(format!("Failed to write attachment file: {e}"))),
+            Sink::Callback { .. } => Ok(()),
+        }
+    }
+}
+
+struct AttachmentStream {
+    sink: Sink,
+    cipher: cbc::Encryptor<Aes256>,
+    hmac: Hmac<Sha256>,
+    /// Plaintext short of a full block, carried into the next feed.
+    pending: Vec<u8>,
+    /// Bytes of the blob written so far, header included.
+    written: u64,
+    file_name: String,
+    key: Option<String>,
+}
+
+/// The keys of a stream about to start: `(data key, encrypted filename, wrapped attachment key)`.
+type StreamKeys = (SymmetricCryptoKey, String, Option<String>);
+
+/// Resolve and check a stream's keys, before anything is written to its sink.
+fn stream_keys(
+    vault_key_b64: &str,
+    wrapped_cipher_key: &str,
+    filename: &str,
+    mode: u32,
+) -> Result<StreamKeys, SdkError> {
+    let vault_key = parse_key(vault_key_b64)?;
+    let keys = attachment_keys(&vault_key, wrapped_cipher_key, filename, mode)?;
+    if !matches!(keys.0, SymmetricCryptoKey::Aes256CbcHmacKey(_)) {
+        return Err(SdkError::invalid_key(
+            "Streaming attachment encryption requires an AES-256-CBC-HMAC key",
+        ));
+    }
+    Ok(keys)
+}
+
+impl AttachmentStream {
+    fn start((data_key, file_name, key): StreamKeys, sink: Sink) -> Result<Self, SdkError> {
+        let key_bytes = data_key.to_encoded();
+        let (enc_key, mac_key) = key_bytes.as_ref().split_at(32);
+
+        let iv: [u8; 16] = rng::random_bytes();
+        let cipher = cbc::Encryptor::<Aes256>::new_from_slices(enc_key, &iv)
+            .expect("AES-256 key and IV lengths are fixed");
+        let mut hmac =
+            Hmac::<Sha256>::new_from_slice(mac_key).expect("HMAC accepts keys of any length");
+        hmac.update(&iv);
+
+        let mut stream = AttachmentStream {
+            sink,
+            cipher,
+            hmac,
+            pending: Vec::with_capacity(BLOCK_LEN),
+            written: 0,
+            file_name,
+            key,
+        };
+
+        // The MAC slot stays zeroed until finalize.
+        let mut header = [0u8; HEADER_LEN as usize];
+        header[0] = 2;
+        header[1..MAC_OFFSET as usize].copy_from_slice(&iv);
+        stream.write(&header)?;
+        Ok(stream)
+    }
+
+    fn feed(&mut self, data: &[u8]) -> Result<(), SdkError> {
+        self.pending.extend_from_slice(data);
+        let full_blocks = self.pending.len() / BLOCK_LEN * BLOCK_LEN;
+        if full_blocks == 0 {
+            return Ok(());
+        }
+
+        let mut blocks: Vec<u8> = self.pending.drain(..full_blocks).collect();
+        self.encrypt_and_write(&mut blocks)
+    }
+
+    /// Pad and encrypt the last block, then fill in the MAC. Returns the blob size.
+    fn finalize(mut self) -> Result<(String, Option<String>, u64), SdkError> {
+        // PKCS#7: always at least one byte of padding, so a full final block gets a block of padding.
+        let padding = (BLOCK_LEN - self.pending.len()) as u8;
+        let mut last = std::mem::take(&mut self.pending);
+        last.resize(BLOCK_LEN, padding);
+        self.encrypt_and_write(&mut last)?;
+
+        let mac = self.hmac.finalize().into_bytes();
+        self.sink.write_at(MAC_OFFSET, &mac)?;
+        self.sink.flush()?;
+
+        Ok((self.file_name, self.key, self.written))
+    }
+
+    fn encrypt_and_write(&mut self, blocks: &mut [u8]) -> Result<(), SdkError> {
+        for block in blocks.chunks_exact_mut(BLOCK_LEN) {
+            self.cipher
+                .encrypt_block(block.try_into().expect("chunks are one block long"));
+        }
+        self.hmac.update(blocks);
+        self.write(blocks)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), SdkError> {
+        self.sink.write_at(self.written, bytes)?;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+/// Start streaming an attachment into the file at `path`, which is created or truncated once the keys
+/// have been checked.
+///
+/// # Arguments
+/// * `vault_key_b64` - Base64-encoded vault key (the user or organization symmetric key)
+/// * `wrapped_cipher_key` - The cipher's wrapped `Key` EncString; only used for v2. Pass "" for none.
+/// * `filename` - The plaintext filename
+/// * `mode` - Attachment scheme version, as for `encrypt_attachment_buffer`
+/// * `path` - Where to write the EncArrayBuffer blob
+///
+/// # Returns
+/// The stream handle, as a decimal string.
+///
+/// # Safety
+/// All pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn attachment_stream_init_file(
+    vault_key_b64: *const c_char,
+    wrapped_cipher_key: *const c_char,
+    filename: *const c_char,
+    mode: u32,
+    path: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(vault_key_b64) = CStr::from_ptr(vault_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("vault_key_b64"));
+        };
+        let Ok(wrapped_cipher_key) = CStr::from_ptr(wrapped_cipher_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("wrapped_cipher_key"));
+        };
+        let Ok(filename) = CStr::from_ptr(filename).to_str() else {
+            return error_response(SdkError::invalid_utf8("filename"));
+        };
+        let Ok(path) = CStr::from_ptr(path).to_str() else {
+            return error_response(SdkError::invalid_utf8("path"));
+        };
+
+        // The file is only created once the keys are known to be good, so bad input leaves it untouched.
+        let result =
+            stream_keys(vault_key_b64, wrapped_cipher_key, filename, mode).and_then(|keys| {
+                let file = File::create(path)
+                    .map_err(|e| SdkError::io(format!("Failed to create attachment file: {e}")))?;
+                let sink = Sink::File {
+                    writer: BufWriter::new(file),
+                    position: 0,
+                };
+                AttachmentStream::start(keys, sink)
+            });
+
+        match result {
+            Ok(stream) => success_response(&register(stream).to_string()),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Start streaming an attachment into a write callback.
+///
+/// `write(context, offset, data, len)` must store `len` bytes at `offset` of the blob and return 0 (or
+/// non-zero to fail the stream). Writes arrive in order, except for the MAC, which is written at offset
+/// 17 when the stream is finalized.
+///
+/// # Arguments
+/// * `vault_key_b64` - Base64-encoded vault key (the user or organization symmetric key)
+/// * `wrapped_cipher_key` - The cipher's wrapped `Key` EncString; only used for v2. Pass "" for none.
+/// * `filename` - The plaintext filename
+/// * `mode` - Attachment scheme version, as for `encrypt_attachment_buffer`
+/// * `write` - The write callback
+/// * `context` - Passed through to every `write` call
+///
+/// # Returns
+/// The stream handle, as a decimal string.
+///
+/// # Safety
+/// The string pointers must be valid null-terminated strings, and `write` must be safe to call with
+/// `context` until the stream is finalized or aborted.
+#[no_mangle]
+pub unsafe extern "C" fn attachment_stream_init_callback(
+    vault_key_b64: *const c_char,
+    wrapped_cipher_key: *const c_char,
+    filename: *const c_char,
+    mode: u32,
+    write: extern "C" fn(*mut c_void, u64, *const u8, usize) -> i32,
+    context: *mut c_void,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(vault_key_b64) = CStr::from_ptr(vault_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("vault_key_b64"));
+        };
+        let Ok(wrapped_cipher_key) = CStr::from_ptr(wrapped_cipher_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("wrapped_cipher_key"));
+        };
+        let Ok(filename) = CStr::from_ptr(filename).to_str() else {
+            return error_response(SdkError::invalid_utf8("filename"));
+        };
+
+        let sink = Sink::Callback { write, context };
+        let result = stream_keys(vault_key_b64, wrapped_cipher_key, filename, mode)
+            .and_then(|keys| AttachmentStream::start(keys, sink));
+
+        match result {
+            Ok(stream) => success_response(&register(stream).to_string()),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Encrypt the next chunk of plaintext. Chunks may be any size.
+///
+/// # Returns
+/// An empty payload. On failure the stream is dropped. While the chunk is being encrypted and written
+/// the handle is not usable, so the stream's write callback must not call back into it.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes, or be null when `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn attachment_stream_feed(
+    handle: u64,
+    data: *const u8,
+    len: usize,
+) -> *const c_char {
+    ffi_boundary(|| {
+        // Fed outside the lock, so a slow sink or callback does not hold up other streams.
+        let Some(mut stream) = take(handle) else {
+            return error_response(invalid_handle(handle));
+        };
+
+        match stream.feed(byte_slice(data, len)) {
+            Ok(()) => {
+                restore(handle, stream);
+                success_response("")
+            }
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Finish a stream: encrypt the final padded block and write the MAC. The handle is released.
+///
+/// # Returns
+/// JSON `{ "key": <EncString|null>, "fileName": <EncString>, "size": <u64> }`, as for
+/// `encrypt_attachment_buffer`.
+#[no_mangle]
+pub unsafe extern "C" fn attachment_stream_finalize(handle: u64) -> *const c_char {
+    ffi_boundary(|| {
+        let Some(stream) = take(handle) else {
+            return error_response(invalid_handle(handle));
+        };
+
+        match stream.finalize() {
+            Ok((file_name, key, size)) => {
+                let json = serde_json::json!({
+                    "key": key,
+                    "fileName": file_name,
+                    "size": size,
+                })
+                .to_string();
+                success_response(&json)
+            }
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Drop a stream without finalizing it. Whatever was written so far is left as is.
+///
+/// # Returns
+/// Whether the handle was an open stream.
+#[no_mangle]
+pub unsafe extern "C" fn attachment_stream_abort(handle: u64) -> bool {
+    take(handle).is_some()
+}
+
+fn register(stream: AttachmentStream) -> u64 {
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    restore(handle, stream);
+    handle
+}
+
+fn restore(handle: u64, stream: AttachmentStream) {
+    STREAMS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(handle, stream);
+}
+
+fn invalid_handle(handle: u64) -> SdkError {
+    SdkError::invalid_input(format!("Invalid attachment stream handle: {handle}"))
+}
+
+fn take(handle: u64) -> Option<AttachmentStream> {
+    STREAMS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attachment::{decrypt_buffer, decrypt_str, encrypt_attachment_bytes};
+    use crate::crypto_util::{take_response, unwrap_key, wrap_key};
+    use crate::rng::with_test_seed;
+    use bitwarden_crypto::SymmetricKeyAlgorithm;
+    use std::{ffi::CString, fs};
+
+    extern "C" fn write_to_vec(
+        context: *mut c_void,
+        offset: u64,
+        data: *const u8,
+        len: usize,
+    ) -> i32 {
+        let blob = unsafe { &mut *(context as *mut Vec<u8>) };
+        let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+        let end = offset as usize + len;
+        if blob.len() < end {
+            blob.resize(end, 0);
+        }
+        blob[offset as usize..end].copy_from_slice(bytes);
+        0
+    }
+
+    /// Stream `data` in `chunk_len` chunks, returning `(blob, encrypted filename, wrapped key)`.
+    fn stream_to_vec(
+        vault_key: &SymmetricCryptoKey,
+        wrapped_cipher_key: &str,
+        mode: u32,
+        data: &[u8],
+        chunk_len: usize,
+    ) -> (Vec<u8>, String, Option<String>) {
+        let mut blob = Vec::new();
+        let sink = Sink::Callback {
+            write: write_to_vec,
+            context: &mut blob as *mut Vec<u8> as *mut c_void,
+        };
+        let vault_key_b64: String = vault_key.to_base64().into();
+        let keys = stream_keys(&vault_key_b64, wrapped_cipher_key, "big.bin", mode).unwrap();
+        let mut stream = AttachmentStream::start(keys, sink).unwrap();
+        for chunk in data.chunks(chunk_len) {
+            stream.feed(chunk).unwrap();
+        }
+        let (file_name, key, size) = stream.finalize().unwrap();
+        assert_eq!(size, blob.len() as u64);
+        (blob, file_name, key)
+    }
+
+    #[test]
+    fn streamed_blob_decrypts_like_a_buffered_one() {
+        let vault_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let data: Vec<u8> = (0..100_003u32).map(|i| (i % 251) as u8).collect();
+
+        for chunk_len in [1, 16, 4096, data.len() + 1] {
+            let (blob, _, key) = stream_to_vec(&vault_key, "", 0, &data, chunk_len);
+
+            assert!(key.is_none(), "v0 has no attachment key");
+            assert_eq!(
+                blob.len() as u64,
+                HEADER_LEN + (data.len() as u64 / 16 + 1) * 16
+            );
+            assert_eq!(decrypt_buffer(&blob, &vault_key).unwrap(), data);
+        }
+    }
+    #[test]
+    fn streamed_v2_blob_decrypts_with_the_cipher_wrapped_key() {
+        let vault_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let cipher_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let wrapped_cipher_key = wrap_key(&cipher_key, &vault_key).unwrap();
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 7) as u8).collect();
+
+        let (blob, file_name, key) = stream_to_vec(&vault_key, &wrapped_cipher_key, 2, &data, 1000);
+
+        let attachment_key =
+            unwrap_key(&key.expect("v2 has an attachment key"), &cipher_key).unwrap();
+        assert_eq!(decrypt_buffer(&blob, &attachment_key).unwrap(), data);
+        assert_eq!(decrypt_str(&file_name, &cipher_key).unwrap(), "big.bin");
+        assert!(decrypt_buffer(&blob, &vault_key).is_err());
+    }
+
+    #[test]
+    fn seeded_stream_matches_seeded_buffered_encryption() {
+        let vault_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let data: Vec<u8> = (0..5_000u32).map(|i| (i % 13) as u8).collect();
+
+        for mode in [0, 1] {
+            let streamed = with_test_seed(5, || stream_to_vec(&vault_key, "", mode, &data, 333));
+            let buffered = with_test_seed(5, || {
+                encrypt_attachment_bytes(&data, &vault_key, "", "big.bin", mode).unwrap()
+            });
+            assert_eq!(streamed, buffered, "mode {mode}");
+        }
+    }
+
+    #[test]
+    fn init_file_leaves_the_file_untouched_on_bad_input() {
+        let path = std::env::temp_dir().join(format!(
+            "attachment_stream_{}_untouched.bin",
+            std::process::id()
+        ));
+        fs::write(&path, b"existing").unwrap();
+        let vault_key_b64 = CString::new(<String>::from(
+            SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac).to_base64(),
+        ))
+        .unwrap();
+        let not_base64 = CString::new("not base64!").unwrap();
+        let no_cipher_key = CString::new("").unwrap();
+        let filename = CString::new("a.bin").unwrap();
+        let path_c = CString::new(path.to_str().unwrap()).unwrap();
+        let init = |vault_key_b64: &CString, mode: u32| {
+            take_response(unsafe {
+                attachment_stream_init_file(
+                    vault_key_b64.as_ptr(),
+                    no_cipher_key.as_ptr(),
+                    filename.as_ptr(),
+                    mode,
+                    path_c.as_ptr(),
+                )
+            })
+        };
+
+        let bad_key = init(&not_base64, 0);
+        let bad_mode = init(&vault_key_b64, 9);
+        let contents = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(bad_key, Err("invalidBase64".to_string()));
+        assert_eq!(bad_mode, Err("unsupportedMode".to_string()));
+        assert_eq!(contents, b"existing");
+    }
+
+    #[test]
+    fn feed_leaves_the_stream_open_and_rejects_unknown_handles() {
+        let vault_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let mut blob = Vec::new();
+        let sink = Sink::Callback {
+            write: write_to_vec,
+            context: &mut blob as *mut Vec<u8> as *mut c_void,
+        };
+        let vault_key_b64: String = vault_key.to_base64().into();
+        let keys = stream_keys(&vault_key_b64, "", "a.bin", 0).unwrap();
+        let handle = register(AttachmentStream::start(keys, sink).unwrap());
+
+        let fed = take_response(unsafe { attachment_stream_feed(handle, b"hello".as_ptr(), 5) });
+        assert_eq!(fed, Ok(String::new()));
+        assert!(take_response(unsafe { attachment_stream_finalize(handle) }).is_ok());
+        assert_eq!(blob.len() as u64, HEADER_LEN + 16);
+        assert_eq!(decrypt_buffer(&blob, &vault_key).unwrap(), b"hello");
+
+        assert_eq!(
+            take_response(unsafe { attachment_stream_feed(handle, b"more".as_ptr(), 4) }),
+            Err("invalidInput".to_string())
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Login-with-device (auth request) key material for the Seeder.
+//!
+//! The requesting device generates an ephemeral RSA keypair and an access code, and sends the public
+//! key and access code with its `AuthRequest`. An approving device encapsulates the user key (or, for
+//! legacy requests, the master key) to that public key and stores it as `AuthRequest.Key`; only the
+//! requesting device, holding the private key, can decapsulate it.
+
+use std::ffi::{c_char, CStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::crypto_util::{
+    encapsulate_key, error_response, ffi_boundary, parse_key, parse_public_key, success_response,
+    SdkError,
+};
+use crate::{pool_material, rng};
+
+/// Length of the access code clients show the user and send as `AuthRequest.AccessCode`.
+const ACCESS_CODE_LENGTH: usize = 25;
+const ACCESS_CODE_ALPHABET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Generate the requesting device's side of an auth request.
+///
+/// The "ephemeral" keypair is drawn from the pre-generated RSA pool, so seeding many requests costs
+/// no RSA generation.
+///
+/// # Arguments
+/// * `pool_index` - Index into the pre-generated RSA keypair pool for the request keypair
+///
+/// # Returns
+/// JSON `{ "publicKey": <base64 SPKI>, "privateKey": <base64 PKCS#8>, "accessCode": <string> }`.
+/// `publicKey` and `accessCode` map onto the `AuthRequest` columns of the same names; `privateKey`
+/// is what the requesting device keeps in memory to decapsulate the approval.
+#[no_mangle]
+pub unsafe extern "C" fn generate_auth_request(pool_index: u32) -> *const c_char {
+    ffi_boundary(|| {
+        let material = pool_material(pool_index);
+
+        let result = serde_json::json!({
+            "publicKey": STANDARD.encode(material.public_der.as_ref()),
+            "privateKey": STANDARD.encode(material.private_der.as_ref()),
+            "accessCode": make_access_code(),
+        });
+
+        success_response(&result.to_string())
+    })
+}
+
+/// Approve an auth request: encapsulate the approving user's key to the request public key.
+///
+/// # Arguments
+/// * `request_public_key_b64` - The `AuthRequest.PublicKey` of the request being approved
+/// * `key_b64` - Base64-encoded user key, or the 32-byte master key for legacy requests
+///
+/// # Returns
+/// The `AuthRequest.Key` EncString, in format "4.{data}"
+///
+/// # Safety
+/// Both pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn approve_auth_request(
+    request_public_key_b64: *const c_char,
+    key_b64: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(request_public_key_b64) = CStr::from_ptr(request_public_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("request_public_key_b64"));
+        };
+        let Ok(key_b64) = CStr::from_ptr(key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("key_b64"));
+        };
+
+        let result = parse_key(key_b64)
+            .and_then(|key| encapsulate_key(&key, &parse_public_key(request_public_key_b64)?));
+
+        match result {
+            Ok(encapsulated) => success_response(&encapsulated),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn make_access_code() -> String {
+    rng::random_bytes::<ACCESS_CODE_LENGTH>()
+        .iter()
+        .map(|b| ACCESS_CODE_ALPHABET[*b as usize % ACCESS_CODE_ALPHABET.len()] as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::{decapsulate_key, take_response};
+    use bitwarden_crypto::{
+        Pkcs8PrivateKeyBytes, PrivateKey, SymmetricCryptoKey, SymmetricKeyAlgorithm,
+    };
+    use std::ffi::CString;
+
+    fn approve_and_decapsulate(key_b64: &str) -> SymmetricCryptoKey {
+        let ptr = unsafe { generate_auth_request(21) };
+        let request: serde_json::Value =
+            serde_json::from_str(&take_response(ptr).unwrap()).unwrap();
+
+        let public_key = CString::new(request["publicKey"].as_str().unwrap()).unwrap();
+        let key = CString::new(key_b64).unwrap();
+        let ptr = unsafe { approve_auth_request(public_key.as_ptr(), key.as_ptr()) };
+        let approved = take_response(ptr).unwrap();
+        assert!(
+            approved.starts_with("4."),
+            "Expected an RSA-OAEP EncString, got: {approved}"
+        );
+
+        // The requesting device decapsulates with the private key it kept.
+        let private_der = STANDARD
+            .decode(request["privateKey"].as_str().unwrap())
+            .unwrap();
+        let private_key = PrivateKey::from_der(&Pkcs8PrivateKeyBytes::from(private_der)).unwrap();
+        decapsulate_key(&approved, &private_key).unwrap()
+    }
+
+    #[test]
+    fn approved_user_key_decapsulates_with_request_private_key() {
+        let user_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let user_key_b64: String = user_key.to_base64().into();
+
+        let recovered = approve_and_decapsulate(&user_key_b64);
+        assert_eq!(<String>::from(recovered.to_base64()), user_key_b64);
+    }
+
+    #[test]
+    fn approved_legacy_master_key_decapsulates_with_request_private_key() {
+        let master_key_b64 = STANDARD.encode([42u8; 32]);
+
+        let recovered = approve_and_decapsulate(&master_key_b64);
+        assert_eq!(<String>::from(recovered.to_base64()), master_key_b64);
+    }
+
+    #[test]
+    fn access_code_is_alphanumeric_of_fixed_length() {
+        let code = make_access_code();
+        assert_eq!(code.len(), ACCESS_CODE_LENGTH);
+        assert!(code.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+}
@@ -4,16 +4,35 @@
 //! values and JSON fields using AES-256-CBC-HMAC-SHA256 via bitwarden_crypto.
 //! No dependency on bitwarden_vault types — the caller drives which fields to encrypt.
 
-use std::ffi::{c_char, CStr, CString};
+use std::{
+    collections::HashMap,
+    ffi::{c_char, CStr},
+};
 
-use base64::{engine::general_purpose::STANDARD, Engine};
+use rayon::prelude::*;
+use serde::Deserialize;
 
-use bitwarden_crypto::{
-    BitwardenLegacyKeyBytes, EncString, KeyDecryptable, KeyEncryptable, SymmetricCryptoKey,
-    SymmetricKeyAlgorithm,
-};
+use bitwarden_crypto::{EncString, KeyDecryptable, SymmetricCryptoKey};
 
-use crate::crypto_util::{error_response, parse_key, wrap_key};
+use crate::crypto_util::{
+    error_response, ffi_boundary, parse_key, success_response, unwrap_key, wrap_key, ErrorKind,
+    SdkError,
+};
+use crate::key_handle::resolve_key;
+use crate::rng;
+
+/// `CipherEncryptionType` in the Seeder: fields encrypted directly with the vault key.
+const MODE_USER_KEY: u32 = 0;
+/// `CipherEncryptionType` in the Seeder: fields encrypted with a per-cipher key wrapped by the vault key.
+const MODE_CIPHER_KEY: u32 = 1;
+
+#[derive(Deserialize)]
+struct BulkCipher {
+    view: serde_json::Value,
+    key: String,
+    #[serde(default)]
+    mode: u32,
+}
 
 /// Encrypt a plaintext string with a symmetric key, returning an EncString.
 ///
@@ -31,29 +50,26 @@ pub unsafe extern "C" fn encrypt_string(
     plaintext: *const c_char,
     symmetric_key_b64: *const c_char,
 ) -> *const c_char {
-    let Ok(plaintext) = CStr::from_ptr(plaintext).to_str() else {
-        return error_response("Invalid UTF-8 in plaintext");
-    };
-
-    let Ok(key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
-        return error_response("Invalid UTF-8 in symmetric_key_b64");
-    };
+    ffi_boundary(|| {
+        let Ok(plaintext) = CStr::from_ptr(plaintext).to_str() else {
+            return error_response(SdkError::invalid_utf8("plaintext"));
+        };
 
-    let Ok(key_bytes) = STANDARD.decode(key_b64) else {
-        return error_response("Failed to decode base64 key");
-    };
+        let Ok(key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("symmetric_key_b64"));
+        };
 
-    let Ok(key) =
-        SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(key_bytes.as_slice()))
-    else {
-        return error_response("Failed to create symmetric key: invalid key format or length");
-    };
+        let key = match parse_key(key_b64) {
+            Ok(key) => key,
+            Err(err) => return error_response(err),
+        };
 
-    let Ok(encrypted) = plaintext.to_string().encrypt_with_key(&key) else {
-        return error_response("Failed to encrypt string");
-    };
+        let Ok(encrypted) = rng::encrypt(plaintext.to_string(), &key) else {
+            return error_response(SdkError::crypto_failure("Failed to encrypt string"));
+        };
 
-    CString::new(encrypted.to_string()).unwrap().into_raw()
+        success_response(&encrypted.to_string())
+    })
 }
 
 /// Decrypt an EncString with a symmetric key, returning the plaintext.
@@ -72,33 +88,33 @@ pub unsafe extern "C" fn decrypt_string(
     enc_string: *const c_char,
     symmetric_key_b64: *const c_char,
 ) -> *const c_char {
-    let Ok(enc_str) = CStr::from_ptr(enc_string).to_str() else {
-        return error_response("Invalid UTF-8 in enc_string");
-    };
-
-    let Ok(key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
-        return error_response("Invalid UTF-8 in symmetric_key_b64");
-    };
-
-    let Ok(parsed): Result<EncString, _> = enc_str.parse() else {
-        return error_response("Failed to parse EncString");
-    };
+    ffi_boundary(|| {
+        let Ok(enc_str) = CStr::from_ptr(enc_string).to_str() else {
+            return error_response(SdkError::invalid_utf8("enc_string"));
+        };
 
-    let Ok(key_bytes) = STANDARD.decode(key_b64) else {
-        return error_response("Failed to decode base64 key");
-    };
+        let Ok(key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("symmetric_key_b64"));
+        };
 
-    let Ok(key) =
-        SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(key_bytes.as_slice()))
-    else {
-        return error_response("Failed to create symmetric key: invalid key format or length");
-    };
+        let Ok(parsed): Result<EncString, _> = enc_str.parse() else {
+            return error_response(SdkError::invalid_input("Failed to parse EncString"));
+        };
 
-    let Ok(plaintext): Result<String, _> = parsed.decrypt_with_key(&key) else {
-        return error_response("Failed to decrypt string");
-    };
+        let key = match parse_key(key_b64) {
+            Ok(key) => key,
+            Err(err) => return error_response(err),
+        };
 
-    CString::new(plaintext).unwrap().into_raw()
+        let plaintext: Result<String, _> = parsed.decrypt_with_key(&key);
+        match plaintext.map_err(SdkError::from_crypto(
+            ErrorKind::DecryptionFailed,
+            "Failed to decrypt string",
+        )) {
+            Ok(plaintext) => success_response(&plaintext),
+            Err(err) => error_response(err),
+        }
+    })
 }
 
 /// Encrypt specified fields in a JSON object, returning the modified JSON.
@@ -123,46 +139,43 @@ pub unsafe extern "C" fn encrypt_fields(
     field_paths_json: *const c_char,
     symmetric_key_b64: *const c_char,
 ) -> *const c_char {
-    let Ok(json_str) = CStr::from_ptr(json).to_str() else {
-        return error_response("Invalid UTF-8 in json");
-    };
-
-    let Ok(paths_str) = CStr::from_ptr(field_paths_json).to_str() else {
-        return error_response("Invalid UTF-8 in field_paths_json");
-    };
+    ffi_boundary(|| {
+        let Ok(json_str) = CStr::from_ptr(json).to_str() else {
+            return error_response(SdkError::invalid_utf8("json"));
+        };
 
-    let Ok(key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
-        return error_response("Invalid UTF-8 in symmetric_key_b64");
-    };
+        let Ok(paths_str) = CStr::from_ptr(field_paths_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("field_paths_json"));
+        };
 
-    let Ok(mut value): Result<serde_json::Value, _> = serde_json::from_str(json_str) else {
-        return error_response("Failed to parse JSON");
-    };
+        let Ok(key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("symmetric_key_b64"));
+        };
 
-    let Ok(paths): Result<Vec<String>, _> = serde_json::from_str(paths_str) else {
-        return error_response("Failed to parse field paths JSON");
-    };
+        let Ok(mut value): Result<serde_json::Value, _> = serde_json::from_str(json_str) else {
+            return error_response(SdkError::invalid_input("Failed to parse JSON"));
+        };
 
-    let Ok(key_bytes) = STANDARD.decode(key_b64) else {
-        return error_response("Failed to decode base64 key");
-    };
+        let Ok(paths): Result<Vec<String>, _> = serde_json::from_str(paths_str) else {
+            return error_response(SdkError::invalid_input("Failed to parse field paths JSON"));
+        };
 
-    let Ok(key) =
-        SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(key_bytes.as_slice()))
-    else {
-        return error_response("Failed to create symmetric key: invalid key format or length");
-    };
+        let key = match parse_key(key_b64) {
+            Ok(key) => key,
+            Err(err) => return error_response(err),
+        };
 
-    for path in &paths {
-        if let Err(msg) = encrypt_at_path(&mut value, path, &key) {
-            return error_response(&msg);
+        for path in &paths {
+            if let Err(err) = encrypt_at_path(&mut value, path, &key) {
+                return error_response(err);
+            }
         }
-    }
 
-    match serde_json::to_string(&value) {
-        Ok(result) => CString::new(result).unwrap().into_raw(),
-        Err(_) => error_response("Failed to serialize result JSON"),
-    }
+        match serde_json::to_string(&value) {
+            Ok(result) => success_response(&result),
+            Err(_) => error_response(SdkError::crypto_failure("Failed to serialize result JSON")),
+        }
+    })
 }
 
 /// Walks a JSON value tree and encrypts string values at the given dot-path.
@@ -171,16 +184,41 @@ fn encrypt_at_path(
     value: &mut serde_json::Value,
     path: &str,
     key: &SymmetricCryptoKey,
-) -> Result<(), String> {
+) -> Result<(), SdkError> {
     let segments: Vec<&str> = path.split('.').collect();
-    encrypt_segments(value, &segments, key)
+    map_segments(value, &segments, &|segment, s| {
+        let encrypted = rng::encrypt(s.to_string(), key).map_err(|_| {
+            SdkError::crypto_failure(format!("Failed to encrypt field '{segment}'"))
+        })?;
+        Ok(encrypted.to_string())
+    })
 }
 
-fn encrypt_segments(
+/// Walks a JSON value tree and decrypts EncString values at the given dot-path.
+/// Supports `[*]` segments for iterating array elements.
+fn decrypt_at_path(
     value: &mut serde_json::Value,
-    segments: &[&str],
+    path: &str,
     key: &SymmetricCryptoKey,
-) -> Result<(), String> {
+) -> Result<(), SdkError> {
+    let segments: Vec<&str> = path.split('.').collect();
+    map_segments(value, &segments, &|segment, s| {
+        let parsed: EncString = s.parse().map_err(|_| {
+            SdkError::invalid_input(format!("Field '{segment}' is not an EncString"))
+        })?;
+        parsed.decrypt_with_key(key).map_err(SdkError::from_crypto(
+            ErrorKind::DecryptionFailed,
+            format!("Failed to decrypt field '{segment}'"),
+        ))
+    })
+}
+
+/// Replace the string values at the path given by `segments` with `f(segment, value)`.
+pub(crate) fn map_segments(
+    value: &mut serde_json::Value,
+    segments: &[&str],
+    f: &dyn Fn(&str, &str) -> Result<String, SdkError>,
+) -> Result<(), SdkError> {
     if segments.is_empty() {
         return Ok(());
     }
@@ -195,20 +233,16 @@ fn encrypt_segments(
         };
 
         for element in arr.iter_mut() {
-            encrypt_segments(element, rest, key)?;
+            map_segments(element, rest, f)?;
         }
 
         return Ok(());
     }
 
-    // Last segment — encrypt the value if it's a string
+    // Last segment — transform the value if it's a string
     if rest.is_empty() {
         if let Some(s) = value.get(segment).and_then(|v| v.as_str()) {
-            let encrypted = s
-                .to_string()
-                .encrypt_with_key(key)
-                .map_err(|_| format!("Failed to encrypt field '{segment}'"))?;
-            value[segment] = serde_json::Value::String(encrypted.to_string());
+            value[segment] = serde_json::Value::String(f(segment, s)?);
         }
         // null or missing — leave unchanged
         return Ok(());
@@ -219,7 +253,7 @@ fn encrypt_segments(
         return Ok(()); // Field missing — skip
     };
 
-    encrypt_segments(nested, rest, key)
+    map_segments(nested, rest, f)
 }
 
 /// Encrypt specified JSON fields under a freshly generated per-cipher key, and return the modified
@@ -242,80 +276,315 @@ pub unsafe extern "C" fn encrypt_fields_with_cipher_key(
     field_paths_json: *const c_char,
     symmetric_key_b64: *const c_char,
 ) -> *const c_char {
-    let Ok(json_str) = CStr::from_ptr(json).to_str() else {
-        return error_response("Invalid UTF-8 in json");
-    };
-    let Ok(paths_str) = CStr::from_ptr(field_paths_json).to_str() else {
-        return error_response("Invalid UTF-8 in field_paths_json");
-    };
-    let Ok(vault_key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
-        return error_response("Invalid UTF-8 in symmetric_key_b64");
-    };
+    ffi_boundary(|| {
+        let Ok(json_str) = CStr::from_ptr(json).to_str() else {
+            return error_response(SdkError::invalid_utf8("json"));
+        };
+        let Ok(paths_str) = CStr::from_ptr(field_paths_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("field_paths_json"));
+        };
+        let Ok(vault_key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("symmetric_key_b64"));
+        };
 
-    match encrypt_fields_with_cipher_key_internal(json_str, paths_str, vault_key_b64) {
-        Ok(json) => CString::new(json).unwrap().into_raw(),
-        Err(msg) => error_response(&msg),
-    }
+        match encrypt_fields_with_cipher_key_internal(json_str, paths_str, vault_key_b64) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
 }
 
 fn encrypt_fields_with_cipher_key_internal(
     json_str: &str,
     paths_str: &str,
     vault_key_b64: &str,
-) -> Result<String, String> {
-    let mut value: serde_json::Value =
-        serde_json::from_str(json_str).map_err(|_| "Failed to parse JSON".to_string())?;
+) -> Result<String, SdkError> {
+    let mut value: serde_json::Value = serde_json::from_str(json_str)
+        .map_err(|_| SdkError::invalid_input("Failed to parse JSON"))?;
     let paths: Vec<String> = serde_json::from_str(paths_str)
-        .map_err(|_| "Failed to parse field paths JSON".to_string())?;
+        .map_err(|_| SdkError::invalid_input("Failed to parse field paths JSON"))?;
     let vault_key = parse_key(vault_key_b64)?;
 
-    // Generate a per-cipher key and encrypt the fields with it (not the vault key directly).
-    let cipher_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
-    for path in &paths {
-        encrypt_at_path(&mut value, path, &cipher_key)?;
+    encrypt_cipher_view(&mut value, &paths, &vault_key, MODE_CIPHER_KEY)?;
+
+    serde_json::to_string(&value)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize result JSON"))
+}
+
+/// Encrypt the fields of one cipher view in the given `CipherEncryptionType` mode.
+fn encrypt_cipher_view(
+    value: &mut serde_json::Value,
+    paths: &[String],
+    vault_key: &SymmetricCryptoKey,
+    mode: u32,
+) -> Result<(), SdkError> {
+    match mode {
+        MODE_USER_KEY => paths
+            .iter()
+            .try_for_each(|path| encrypt_at_path(value, path, vault_key)),
+        MODE_CIPHER_KEY => {
+            // Generate a per-cipher key and encrypt the fields with it (not the vault key directly).
+            let cipher_key = rng::make_symmetric_key();
+            for path in paths {
+                encrypt_at_path(value, path, &cipher_key)?;
+            }
+
+            // The cipher key is wrapped by the vault key and stored on the cipher as `key`.
+            let Some(object) = value.as_object_mut() else {
+                return Err(SdkError::invalid_input("Cipher view must be a JSON object"));
+            };
+            object.insert(
+                "key".to_string(),
+                serde_json::Value::String(wrap_key(&cipher_key, vault_key)?),
+            );
+            Ok(())
+        }
+        _ => Err(SdkError::unsupported_mode(format!(
+            "Unsupported cipher encryption mode: {mode}"
+        ))),
     }
+}
 
-    // The cipher key is wrapped by the vault key and stored on the cipher as `key`.
-    value["key"] = serde_json::Value::String(wrap_key(&cipher_key, &vault_key)?);
+/// Encrypt a batch of cipher views in one call, spread across all cores.
+///
+/// Each cipher comes out exactly as `encrypt_fields` (mode 0) or `encrypt_fields_with_cipher_key`
+/// (mode 1) would produce it, but the field paths are parsed and each distinct key is decoded once
+/// for the whole batch. While `set_rng_seed` is in effect the batch runs on the calling thread
+/// instead, since the seeded stream is only reproducible when drawn in call order.
+///
+/// # Arguments
+/// * `ciphers_json` - JSON array `[{ "view": <cipher view>, "key": <base64 vault key>, "mode": 0 | 1 }]`,
+///   where `mode` is the Seeder's `CipherEncryptionType` (0 = `UserKey`, the default; 1 = `CipherKey`)
+/// * `field_paths_json` - JSON array of dot-notation field paths, shared by every cipher
+///
+/// # Returns
+/// JSON array of the encrypted cipher objects, in input order. The first failing cipher fails the
+/// whole batch, with its index in the message.
+///
+/// # Safety
+/// Both pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn encrypt_ciphers_bulk(
+    ciphers_json: *const c_char,
+    field_paths_json: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(ciphers_json) = CStr::from_ptr(ciphers_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("ciphers_json"));
+        };
+        let Ok(paths_str) = CStr::from_ptr(field_paths_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("field_paths_json"));
+        };
 
-    serde_json::to_string(&value).map_err(|_| "Failed to serialize result JSON".to_string())
+        match encrypt_ciphers_bulk_internal(ciphers_json, paths_str) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::crypto_util::unwrap_key;
-    use crate::free_c_string;
+fn encrypt_ciphers_bulk_internal(ciphers_json: &str, paths_str: &str) -> Result<String, SdkError> {
+    let ciphers: Vec<BulkCipher> = serde_json::from_str(ciphers_json)
+        .map_err(|e| SdkError::invalid_input(format!("Invalid bulk cipher JSON: {e}")))?;
+    let paths: Vec<String> = serde_json::from_str(paths_str)
+        .map_err(|_| SdkError::invalid_input("Failed to parse field paths JSON"))?;
 
-    fn make_test_key() -> SymmetricCryptoKey {
-        SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac)
+    let mut keys = HashMap::new();
+    for cipher in &ciphers {
+        if !keys.contains_key(&cipher.key) {
+            keys.insert(cipher.key.clone(), parse_key(&cipher.key)?);
+        }
     }
 
-    fn call_ffi_string(
-        func: unsafe extern "C" fn(*const c_char, *const c_char) -> *const c_char,
-        a: &str,
-        b: &str,
-    ) -> String {
-        let a_cstr = CString::new(a).unwrap();
-        let b_cstr = CString::new(b).unwrap();
-        let ptr = unsafe { func(a_cstr.as_ptr(), b_cstr.as_ptr()) };
-        let result = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_owned();
-        unsafe { free_c_string(ptr as *mut c_char) };
-        result
+    let encrypt = |(index, cipher): (usize, BulkCipher)| {
+        let mut view = cipher.view;
+        encrypt_cipher_view(&mut view, &paths, &keys[&cipher.key], cipher.mode)
+            .map_err(|err| SdkError::new(err.kind, format!("Cipher {index}: {err}")))?;
+        Ok::<_, SdkError>(view)
+    };
+
+    let encrypted: Result<Vec<serde_json::Value>, SdkError> = if rng::is_seeded() {
+        ciphers.into_iter().enumerate().map(encrypt).collect()
+    } else {
+        ciphers.into_par_iter().enumerate().map(encrypt).collect()
+    };
+
+    serde_json::to_string(&encrypted?)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize result JSON"))
+}
+
+/// Encrypt a plaintext string with a registered key: `encrypt_string` by handle.
+///
+/// # Arguments
+/// * `plaintext` - The plaintext string to encrypt
+/// * `key_handle` - Handle from `register_key`
+///
+/// # Returns
+/// EncString in format "2.{iv}|{data}|{mac}"
+///
+/// # Safety
+/// `plaintext` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn encrypt_string_with_handle(
+    plaintext: *const c_char,
+    key_handle: u64,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(plaintext) = CStr::from_ptr(plaintext).to_str() else {
+            return error_response(SdkError::invalid_utf8("plaintext"));
+        };
+
+        let result = resolve_key(key_handle).and_then(|key| {
+            rng::encrypt(plaintext.to_string(), &key)
+                .map_err(|_| SdkError::crypto_failure("Failed to encrypt string"))
+        });
+
+        match result {
+            Ok(encrypted) => success_response(&encrypted.to_string()),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Encrypt the fields of a cipher view with a registered vault key: `encrypt_fields` (mode 0) or
+/// `encrypt_fields_with_cipher_key` (mode 1) by handle.
+///
+/// # Arguments
+/// * `json` - JSON object string (the cipher view)
+/// * `field_paths_json` - JSON array of dot-notation field paths
+/// * `key_handle` - Handle from `register_key` for the vault key
+/// * `mode` - The Seeder's `CipherEncryptionType`: 0 = `UserKey`, 1 = `CipherKey`
+///
+/// # Returns
+/// Modified JSON with matching string fields encrypted, plus the wrapped cipher key as `key` in mode 1
+///
+/// # Safety
+/// `json` and `field_paths_json` must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn encrypt_fields_with_handle(
+    json: *const c_char,
+    field_paths_json: *const c_char,
+    key_handle: u64,
+    mode: u32,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(json_str) = CStr::from_ptr(json).to_str() else {
+            return error_response(SdkError::invalid_utf8("json"));
+        };
+        let Ok(paths_str) = CStr::from_ptr(field_paths_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("field_paths_json"));
+        };
+
+        match encrypt_fields_with_handle_internal(json_str, paths_str, key_handle, mode) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn encrypt_fields_with_handle_internal(
+    json_str: &str,
+    paths_str: &str,
+    key_handle: u64,
+    mode: u32,
+) -> Result<String, SdkError> {
+    let mut value: serde_json::Value = serde_json::from_str(json_str)
+        .map_err(|_| SdkError::invalid_input("Failed to parse JSON"))?;
+    let paths: Vec<String> = serde_json::from_str(paths_str)
+        .map_err(|_| SdkError::invalid_input("Failed to parse field paths JSON"))?;
+    let vault_key = resolve_key(key_handle)?;
+
+    encrypt_cipher_view(&mut value, &paths, &vault_key, mode)?;
+
+    serde_json::to_string(&value)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize result JSON"))
+}
+
+/// Decrypt specified fields in a JSON object, returning the plaintext JSON view.
+///
+/// The inverse of `encrypt_fields` / `encrypt_fields_with_cipher_key`: takes the same dot-notation
+/// field paths (with `[*]` for array elements) and decrypts the EncString values at matching paths.
+/// Non-string values and unmatched paths are left unchanged.
+///
+/// # Arguments
+/// * `json` - JSON object string (the encrypted cipher)
+/// * `field_paths_json` - JSON array of dot-notation field paths
+/// * `symmetric_key_b64` - Base64-encoded vault key
+/// * `unwrap_cipher_key` - When true and the object has a top-level `key`, unwrap that cipher key with
+///   the vault key and decrypt the fields with it instead. The `key` field itself is left as-is.
+///
+/// # Returns
+/// Modified JSON with matching fields decrypted to plaintext strings
+///
+/// # Safety
+/// All pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn decrypt_fields(
+    json: *const c_char,
+    field_paths_json: *const c_char,
+    symmetric_key_b64: *const c_char,
+    unwrap_cipher_key: bool,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(json_str) = CStr::from_ptr(json).to_str() else {
+            return error_response(SdkError::invalid_utf8("json"));
+        };
+        let Ok(paths_str) = CStr::from_ptr(field_paths_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("field_paths_json"));
+        };
+        let Ok(vault_key_b64) = CStr::from_ptr(symmetric_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("symmetric_key_b64"));
+        };
+
+        match decrypt_fields_internal(json_str, paths_str, vault_key_b64, unwrap_cipher_key) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn decrypt_fields_internal(
+    json_str: &str,
+    paths_str: &str,
+    vault_key_b64: &str,
+    unwrap_cipher_key: bool,
+) -> Result<String, SdkError> {
+    let mut value: serde_json::Value = serde_json::from_str(json_str)
+        .map_err(|_| SdkError::invalid_input("Failed to parse JSON"))?;
+    let paths: Vec<String> = serde_json::from_str(paths_str)
+        .map_err(|_| SdkError::invalid_input("Failed to parse field paths JSON"))?;
+    let vault_key = parse_key(vault_key_b64)?;
+
+    let key = match value.get("key").and_then(|v| v.as_str()) {
+        Some(wrapped) if unwrap_cipher_key => unwrap_key(wrapped, &vault_key)?,
+        _ => vault_key,
+    };
+    for path in &paths {
+        decrypt_at_path(&mut value, path, &key)?;
     }
 
+    serde_json::to_string(&value)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize result JSON"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::{call_ffi_string, decrypt_value, make_test_key, take_response};
+    use std::ffi::CString;
+
     #[test]
     fn encrypt_string_decrypt_string_roundtrip() {
         let key = make_test_key();
         let key_b64: String = key.to_base64().into();
 
-        let encrypted = call_ffi_string(encrypt_string, "hello world", &key_b64);
+        let encrypted = call_ffi_string(encrypt_string, "hello world", &key_b64).unwrap();
         assert!(
             encrypted.starts_with("2."),
             "Expected EncString, got: {encrypted}"
         );
 
-        let decrypted = call_ffi_string(decrypt_string, &encrypted, &key_b64);
+        let decrypted = call_ffi_string(decrypt_string, &encrypted, &key_b64).unwrap();
         assert_eq!(decrypted, "hello world");
     }
 
@@ -393,10 +662,7 @@ mod tests {
 
         let ptr =
             unsafe { encrypt_fields(json_cstr.as_ptr(), paths_cstr.as_ptr(), key_cstr.as_ptr()) };
-        let result = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_owned();
-        unsafe { free_c_string(ptr as *mut c_char) };
-
-        assert!(!result.contains("\"error\""), "Got error: {result}");
+        let result = take_response(ptr).unwrap();
 
         let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
         let name = parsed["name"].as_str().unwrap();
@@ -422,13 +688,10 @@ mod tests {
         let key1_b64: String = key1.to_base64().into();
         let key2_b64: String = key2.to_base64().into();
 
-        let encrypted = call_ffi_string(encrypt_string, "secret", &key1_b64);
+        let encrypted = call_ffi_string(encrypt_string, "secret", &key1_b64).unwrap();
         let result = call_ffi_string(decrypt_string, &encrypted, &key2_b64);
 
-        assert!(
-            result.contains("\"error\""),
-            "Should fail with wrong key, got: {result}"
-        );
+        assert_eq!(result, Err("macFailure".to_string()));
     }
 
     #[test]
@@ -487,4 +750,123 @@ mod tests {
         // Non-encrypted fields are left intact.
         assert_eq!(parsed["type"].as_i64().unwrap(), 1);
     }
+
+    #[test]
+    fn decrypt_fields_inverts_encrypt_fields() {
+        let key = make_test_key();
+        let key_b64: String = key.to_base64().into();
+        let view = serde_json::json!({
+            "name": "Round Trip",
+            "notes": null,
+            "type": 1,
+            "login": {
+                "username": "user@test.com",
+                "uris": [{"uri": "https://example.com", "match": 0}]
+            }
+        });
+        let paths = vec!["name", "notes", "login.username", "login.uris[*].uri"];
+
+        let mut encrypted = view.clone();
+        for path in &paths {
+            encrypt_at_path(&mut encrypted, path, &key).unwrap();
+        }
+        let out = decrypt_fields_internal(
+            &encrypted.to_string(),
+            &serde_json::to_string(&paths).unwrap(),
+            &key_b64,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&out).unwrap(),
+            view
+        );
+    }
+
+    #[test]
+    fn decrypt_fields_unwraps_cipher_key() {
+        let vault = make_test_key();
+        let vault_b64: String = vault.to_base64().into();
+        let input = serde_json::json!({"name": "Cipher-Key Login", "type": 1}).to_string();
+        let paths = r#"["name"]"#;
+
+        let encrypted = encrypt_fields_with_cipher_key_internal(&input, paths, &vault_b64).unwrap();
+
+        let out = decrypt_fields_internal(&encrypted, paths, &vault_b64, true).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed["name"].as_str().unwrap(), "Cipher-Key Login");
+
+        // Without unwrapping, the vault key cannot decrypt cipher-key fields.
+        let err = decrypt_fields_internal(&encrypted, paths, &vault_b64, false).unwrap_err();
+        assert_eq!(err.message, "Failed to decrypt field 'name'");
+        assert_eq!(err.kind, ErrorKind::MacFailure);
+    }
+
+    #[test]
+    fn encrypt_ciphers_bulk_matches_per_cipher_modes() {
+        let user_key = make_test_key();
+        let org_key = make_test_key();
+        let user_b64: String = user_key.to_base64().into();
+        let org_b64: String = org_key.to_base64().into();
+        let ciphers = serde_json::json!([
+            { "view": { "name": "Personal", "type": 1 }, "key": user_b64 },
+            { "view": { "name": "Shared", "type": 1 }, "key": org_b64, "mode": 1 },
+            { "view": { "name": "Also Personal", "type": 2 }, "key": user_b64, "mode": 0 },
+        ]);
+
+        let out = encrypt_ciphers_bulk_internal(&ciphers.to_string(), r#"["name"]"#).unwrap();
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed.len(), 3);
+
+        assert_eq!(decrypt_value(&parsed[0]["name"], &user_key), "Personal");
+        assert!(parsed[0].get("key").is_none());
+        assert_eq!(
+            decrypt_value(&parsed[2]["name"], &user_key),
+            "Also Personal"
+        );
+        assert_eq!(parsed[2]["type"].as_i64().unwrap(), 2);
+
+        // Cipher-key mode: the fields decrypt with the cipher key the organization key unwraps.
+        let cipher_key = unwrap_key(parsed[1]["key"].as_str().unwrap(), &org_key).unwrap();
+        assert_eq!(decrypt_value(&parsed[1]["name"], &cipher_key), "Shared");
+    }
+
+    #[test]
+    fn encrypt_ciphers_bulk_reports_failing_index() {
+        let key_b64: String = make_test_key().to_base64().into();
+        let ciphers = serde_json::json!([
+            { "view": { "name": "Fine" }, "key": key_b64 },
+            { "view": { "name": "Bad" }, "key": key_b64, "mode": 7 },
+        ]);
+
+        let err = encrypt_ciphers_bulk_internal(&ciphers.to_string(), r#"["name"]"#).unwrap_err();
+        assert_eq!(
+            err,
+            SdkError::unsupported_mode("Cipher 1: Unsupported cipher encryption mode: 7")
+        );
+    }
+
+    #[test]
+    fn encrypt_fields_with_handle_uses_registered_key() {
+        let key = make_test_key();
+        let key_b64 = CString::new(<String>::from(key.to_base64())).unwrap();
+        let handle: u64 =
+            take_response(unsafe { crate::key_handle::register_key(key_b64.as_ptr()) })
+                .unwrap()
+                .parse()
+                .unwrap();
+
+        let input = serde_json::json!({"name": "By Handle", "type": 1}).to_string();
+        let out = encrypt_fields_with_handle_internal(&input, r#"["name"]"#, handle, 0).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+        let name: EncString = parsed["name"].as_str().unwrap().parse().unwrap();
+        let name: String = name.decrypt_with_key(&key).unwrap();
+        assert_eq!(name, "By Handle");
+
+        unsafe { crate::key_handle::release_key(handle) };
+        let err =
+            encrypt_fields_with_handle_internal(&input, r#"["name"]"#, handle, 0).unwrap_err();
+        assert_eq!(err.message, format!("Invalid key handle: {handle}"));
+    }
 }
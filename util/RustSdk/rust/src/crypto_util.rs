@@ -1,38 +1,267 @@
-//! Shared crypto plumbing for the Seeder FFI shim: key parsing, key wrapping/unwrapping, and the
-//! error-response helper. Used by both `cipher` and `attachment`.
+//! Shared crypto plumbing for the Seeder FFI shim: key parsing, key wrapping/unwrapping, KDF
+//! construction, and the FFI result envelope. Used by every FFI module.
 
-use std::ffi::{c_char, CString};
+use std::{
+    ffi::{c_char, CString},
+    num::NonZeroU32,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
 
 use base64::{engine::general_purpose::STANDARD, Engine};
 
 use bitwarden_crypto::{
-    BitwardenLegacyKeyBytes, EncString, KeyDecryptable, KeyEncryptable, SymmetricCryptoKey,
+    BitwardenLegacyKeyBytes, CryptoError, EncString, Kdf, KeyDecryptable, Pkcs8PrivateKeyBytes,
+    PrivateKey, PublicKey, SpkiPublicKeyBytes, SymmetricCryptoKey, UnsignedSharedKey,
 };
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+use crate::master_key_cache::derive_master_key;
+use crate::rng;
+
+/// `KdfType.PBKDF2_SHA256` on the server.
+pub(crate) const KDF_PBKDF2: u32 = 0;
+/// `KdfType.Argon2id` on the server.
+pub(crate) const KDF_ARGON2ID: u32 = 1;
+
+/// Machine-readable error kinds of the FFI result envelope. The discriminant is the envelope's
+/// `status`; a status of 0 means success.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ErrorKind {
+    InvalidUtf8 = 1,
+    InvalidBase64 = 2,
+    InvalidKey = 3,
+    /// Decryption or decapsulation failed for a reason other than the MAC check.
+    DecryptionFailed = 4,
+    UnsupportedMode = 5,
+    InvalidInput = 6,
+    CryptoFailure = 7,
+    /// The function panicked; caught at the FFI boundary by [ffi_boundary].
+    Panic = 8,
+    /// Key bytes of a length no key type accepts.
+    InvalidKeyLength = 9,
+    /// A MAC or signature did not verify: usually the wrong key.
+    MacFailure = 10,
+    /// Reading or writing a file, or a caller's write callback, failed.
+    Io = 11,
+}
+
+impl ErrorKind {
+    /// The `errorKind` name in the envelope.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ErrorKind::InvalidUtf8 => "invalidUtf8",
+            ErrorKind::InvalidBase64 => "invalidBase64",
+            ErrorKind::InvalidKey => "invalidKey",
+            ErrorKind::DecryptionFailed => "decryptionFailed",
+            ErrorKind::UnsupportedMode => "unsupportedMode",
+            ErrorKind::InvalidInput => "invalidInput",
+            ErrorKind::CryptoFailure => "cryptoFailure",
+            ErrorKind::Panic => "panic",
+            ErrorKind::InvalidKeyLength => "invalidKeyLength",
+            ErrorKind::MacFailure => "macFailure",
+            ErrorKind::Io => "io",
+        }
+    }
+}
+
+/// An error on its way to the FFI envelope. The kind is chosen where the error is raised, never
+/// inferred from the message, which may quote caller data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SdkError {
+    pub(crate) kind: ErrorKind,
+    pub(crate) message: String,
+}
+
+impl SdkError {
+    pub(crate) fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        SdkError {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// A string argument that is not valid UTF-8.
+    pub(crate) fn invalid_utf8(argument: &str) -> Self {
+        Self::new(
+            ErrorKind::InvalidUtf8,
+            format!("Invalid UTF-8 in {argument}"),
+        )
+    }
+
+    pub(crate) fn invalid_base64(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidBase64, message)
+    }
+
+    pub(crate) fn invalid_key(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidKey, message)
+    }
+
+    pub(crate) fn unsupported_mode(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::UnsupportedMode, message)
+    }
+
+    pub(crate) fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidInput, message)
+    }
+
+    pub(crate) fn crypto_failure(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::CryptoFailure, message)
+    }
+
+    pub(crate) fn io(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Io, message)
+    }
+
+    /// Map a `bitwarden_crypto` error to `kind`, except that a wrong key length and a MAC mismatch
+    /// keep their own kinds. For use with `map_err`.
+    pub(crate) fn from_crypto(
+        kind: ErrorKind,
+        message: impl Into<String>,
+    ) -> impl FnOnce(CryptoError) -> Self {
+        let message = message.into();
+        move |error| {
+            let kind = match error {
+                CryptoError::InvalidKeyLen => ErrorKind::InvalidKeyLength,
+                CryptoError::InvalidMac | CryptoError::MacNotProvided => ErrorKind::MacFailure,
+                _ => kind,
+            };
+            Self::new(kind, message)
+        }
+    }
+}
+
+impl std::fmt::Display for SdkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Wrap a successful result in the FFI envelope and return it as a C string pointer.
+///
+/// Every exported function returns `{ "status": 0, "errorKind": null, "message": null,
+/// "payload": <string> }` on success, where `payload` is the raw result (an EncString, or the JSON
+/// document the function documents, serialized as a string).
+pub(crate) fn success_response(payload: &str) -> *const c_char {
+    envelope(serde_json::json!({
+        "status": 0,
+        "errorKind": null,
+        "message": null,
+        "payload": payload,
+    }))
+}
+
+/// Wrap an error in the FFI envelope and return it as a C string pointer:
+/// `{ "status": <kind>, "errorKind": <name>, "message": <message>, "payload": null }`.
+pub(crate) fn error_response(error: SdkError) -> *const c_char {
+    error_envelope(error.kind, &error.message)
+}
+
+fn error_envelope(kind: ErrorKind, message: &str) -> *const c_char {
+    envelope(serde_json::json!({
+        "status": kind as u32,
+        "errorKind": kind.name(),
+        "message": message,
+        "payload": null,
+    }))
+}
 
-/// Create an error JSON response and return it as a C string pointer.
-pub(crate) fn error_response(message: &str) -> *const c_char {
-    let error_json = serde_json::json!({ "error": message }).to_string();
-    CString::new(error_json).unwrap().into_raw()
+fn envelope(value: serde_json::Value) -> *const c_char {
+    // serde_json escapes NUL, so the serialized envelope never contains an interior NUL byte.
+    CString::new(value.to_string())
+        .expect("serialized JSON has no interior NUL")
+        .into_raw()
+}
+
+/// Run the body of an exported function, turning a panic into a `panic` error envelope.
+///
+/// Unwinding across `extern "C"` is undefined behavior and takes the Seeder process down with it, so
+/// every exported function that returns an envelope runs its body through this.
+pub(crate) fn ffi_boundary(body: impl FnOnce() -> *const c_char) -> *const c_char {
+    catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        error_envelope(ErrorKind::Panic, &format!("Rust SDK panicked: {reason}"))
+    })
+}
+
+/// Read and free an FFI envelope in tests: the payload on success, the `errorKind` on failure.
+#[cfg(test)]
+pub(crate) fn take_response(ptr: *const c_char) -> Result<String, String> {
+    let raw = unsafe { std::ffi::CStr::from_ptr(ptr) }
+        .to_str()
+        .unwrap()
+        .to_owned();
+    unsafe { crate::free_c_string(ptr as *mut c_char) };
+
+    let envelope: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    match envelope["status"].as_u64().unwrap() {
+        0 => Ok(envelope["payload"].as_str().unwrap().to_owned()),
+        _ => Err(envelope["errorKind"].as_str().unwrap().to_owned()),
+    }
+}
+
+/// A fresh AES-256-CBC-HMAC key for tests.
+#[cfg(test)]
+pub(crate) fn make_test_key() -> SymmetricCryptoKey {
+    SymmetricCryptoKey::make(bitwarden_crypto::SymmetricKeyAlgorithm::Aes256CbcHmac)
+}
+
+/// Call a two-string FFI function in tests and take its response, as `take_response` does.
+#[cfg(test)]
+pub(crate) fn call_ffi_string(
+    func: unsafe extern "C" fn(*const c_char, *const c_char) -> *const c_char,
+    a: &str,
+    b: &str,
+) -> Result<String, String> {
+    let a_cstr = CString::new(a).unwrap();
+    let b_cstr = CString::new(b).unwrap();
+    let ptr = unsafe { func(a_cstr.as_ptr(), b_cstr.as_ptr()) };
+    take_response(ptr)
+}
+
+/// Decrypt an EncString held in a JSON result in tests.
+#[cfg(test)]
+pub(crate) fn decrypt_value(enc: &serde_json::Value, key: &SymmetricCryptoKey) -> String {
+    let enc: EncString = enc.as_str().unwrap().parse().unwrap();
+    enc.decrypt_with_key(key).unwrap()
+}
+
+/// Borrow a caller-owned pointer-and-length byte buffer. A null pointer reads as empty.
+///
+/// # Safety
+/// Unless null, `data` must point to `len` readable bytes that outlive the returned slice.
+pub(crate) unsafe fn byte_slice<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if data.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len)
+    }
 }
 
 /// Decode a base64 symmetric key into a [SymmetricCryptoKey].
-pub(crate) fn parse_key(key_b64: &str) -> Result<SymmetricCryptoKey, String> {
+pub(crate) fn parse_key(key_b64: &str) -> Result<SymmetricCryptoKey, SdkError> {
     let key_bytes = STANDARD
         .decode(key_b64)
-        .map_err(|_| "Failed to decode base64 key".to_string())?;
-    SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(key_bytes.as_slice()))
-        .map_err(|_| "Failed to create symmetric key: invalid key format or length".to_string())
+        .map_err(|_| SdkError::invalid_base64("Failed to decode base64 key"))?;
+    SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(key_bytes.as_slice())).map_err(
+        SdkError::from_crypto(
+            ErrorKind::InvalidKey,
+            "Failed to create symmetric key: invalid key format or length",
+        ),
+    )
 }
 
 /// Wrap a symmetric key with another symmetric key, returning the wrapped key as an EncString.
 pub(crate) fn wrap_key(
     key_to_wrap: &SymmetricCryptoKey,
     wrapping_key: &SymmetricCryptoKey,
-) -> Result<String, String> {
-    let wrapped = key_to_wrap
-        .to_encoded()
-        .encrypt_with_key(wrapping_key)
-        .map_err(|_| "Failed to wrap key".to_string())?;
+) -> Result<String, SdkError> {
+    let wrapped = rng::encrypt(&key_to_wrap.to_encoded(), wrapping_key)
+        .map_err(|_| SdkError::crypto_failure("Failed to wrap key"))?;
     Ok(wrapped.to_string())
 }
 
@@ -40,13 +269,207 @@ pub(crate) fn wrap_key(
 pub(crate) fn unwrap_key(
     wrapped: &str,
     wrapping_key: &SymmetricCryptoKey,
-) -> Result<SymmetricCryptoKey, String> {
+) -> Result<SymmetricCryptoKey, SdkError> {
     let parsed: EncString = wrapped
         .parse()
-        .map_err(|_| "Failed to parse wrapped key EncString".to_string())?;
+        .map_err(|_| SdkError::invalid_input("Failed to parse wrapped key EncString"))?;
     let bytes: Vec<u8> = parsed
         .decrypt_with_key(wrapping_key)
-        .map_err(|_| "Failed to unwrap key".to_string())?;
-    SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(bytes.as_slice()))
-        .map_err(|_| "Failed to reconstruct unwrapped key".to_string())
+        .map_err(SdkError::from_crypto(
+            ErrorKind::DecryptionFailed,
+            "Failed to unwrap key",
+        ))?;
+    SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(bytes.as_slice())).map_err(
+        SdkError::from_crypto(ErrorKind::InvalidKey, "Failed to reconstruct unwrapped key"),
+    )
+}
+
+/// Decode a base64 SPKI public key into a [PublicKey].
+pub(crate) fn parse_public_key(public_key_b64: &str) -> Result<PublicKey, SdkError> {
+    let der = STANDARD
+        .decode(public_key_b64)
+        .map_err(|_| SdkError::invalid_base64("Failed to decode base64 public key"))?;
+    PublicKey::from_der(&SpkiPublicKeyBytes::from(der))
+        .map_err(|_| SdkError::invalid_key("Failed to parse public key DER"))
+}
+
+/// Encapsulate a symmetric key to an RSA public key, returning the RSA-2048-OAEP-SHA1 shared key
+/// EncString ("4.{data}").
+pub(crate) fn encapsulate_key(
+    key: &SymmetricCryptoKey,
+    public_key: &PublicKey,
+) -> Result<String, SdkError> {
+    // The pinned bitwarden-crypto has no signed shared-key format, so this is the only encapsulation
+    // the Seeder can produce. A signed variant needs a rev that has one.
+    #[allow(deprecated)]
+    let encapsulated = UnsignedSharedKey::encapsulate_key_unsigned(key, public_key)
+        .map_err(|_| SdkError::crypto_failure("Failed to encapsulate key"))?;
+    Ok(encapsulated.to_string())
+}
+
+/// Decapsulate an RSA shared key EncString ("4.{data}") with the recipient's private key.
+pub(crate) fn decapsulate_key(
+    encapsulated: &str,
+    private_key: &PrivateKey,
+) -> Result<SymmetricCryptoKey, SdkError> {
+    let parsed: UnsignedSharedKey = encapsulated
+        .parse()
+        .map_err(|_| SdkError::invalid_input("Failed to parse shared key EncString"))?;
+    #[allow(deprecated)]
+    let key = parsed.decapsulate_key_unsigned(private_key);
+    key.map_err(SdkError::from_crypto(
+        ErrorKind::DecryptionFailed,
+        "Failed to decapsulate key",
+    ))
+}
+
+/// Decrypt a wrapped PKCS#8 private key EncString with the key that wraps it.
+pub(crate) fn unwrap_private_key(
+    wrapped: &str,
+    wrapping_key: &SymmetricCryptoKey,
+) -> Result<PrivateKey, SdkError> {
+    let parsed: EncString = wrapped
+        .parse()
+        .map_err(|_| SdkError::invalid_input("Failed to parse wrapped private key EncString"))?;
+    let der: Vec<u8> = parsed
+        .decrypt_with_key(wrapping_key)
+        .map_err(SdkError::from_crypto(
+            ErrorKind::DecryptionFailed,
+            "Failed to unwrap private key",
+        ))?;
+    PrivateKey::from_der(&Pkcs8PrivateKeyBytes::from(der))
+        .map_err(|_| SdkError::invalid_key("Failed to parse private key DER"))
+}
+
+/// Derive the master key for a new master password and wrap an existing user key with it, as a
+/// password change does. Returns the server authorization hash and the wrapped user key.
+pub(crate) fn wrap_user_key_with_password(
+    email: &str,
+    password: &str,
+    kdf: &Kdf,
+    user_key: &SymmetricCryptoKey,
+) -> Result<(String, String), SdkError> {
+    let derived = derive_master_key(email, password, kdf)?;
+    let encrypted_user_key = rng::wrap_user_key(&derived.master_key, user_key)?;
+    Ok((
+        derived.master_password_hash.clone(),
+        encrypted_user_key.to_string(),
+    ))
+}
+
+/// Stretch a 32-byte master key into the AES-256-CBC-HMAC key that wraps the user key: HKDF-SHA256
+/// expand of the master key with info "enc" and "mac", as every client does.
+pub(crate) fn stretch_master_key(master_key: &[u8]) -> Result<SymmetricCryptoKey, SdkError> {
+    let hkdf = Hkdf::<Sha256>::from_prk(master_key).map_err(|_| {
+        SdkError::new(
+            ErrorKind::InvalidKeyLength,
+            "Failed to stretch master key: invalid key length",
+        )
+    })?;
+    let mut stretched = [0u8; 64];
+    let (enc_key, mac_key) = stretched.split_at_mut(32);
+    hkdf.expand(b"enc", enc_key)
+        .and_then(|_| hkdf.expand(b"mac", mac_key))
+        .map_err(|_| SdkError::crypto_failure("Failed to stretch master key"))?;
+    SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(stretched.as_slice()))
+        .map_err(|_| SdkError::crypto_failure("Failed to create stretched master key"))
+}
+
+/// Build a [Kdf] from the server's KDF columns. `memory` (MiB) and `parallelism` only apply to
+/// Argon2id and are ignored for PBKDF2.
+pub(crate) fn make_kdf(
+    kdf_type: u32,
+    iterations: u32,
+    memory: u32,
+    parallelism: u32,
+) -> Result<Kdf, SdkError> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| SdkError::invalid_input("kdf_iterations must be non-zero"))?;
+
+    match kdf_type {
+        KDF_PBKDF2 => Ok(Kdf::PBKDF2 { iterations }),
+        KDF_ARGON2ID => Ok(Kdf::Argon2id {
+            iterations,
+            memory: NonZeroU32::new(memory).ok_or_else(|| {
+                SdkError::invalid_input("kdf_memory must be non-zero for Argon2id")
+            })?,
+            parallelism: NonZeroU32::new(parallelism).ok_or_else(|| {
+                SdkError::invalid_input("kdf_parallelism must be non-zero for Argon2id")
+            })?,
+        }),
+        _ => Err(SdkError::unsupported_mode(format!(
+            "Unsupported kdf_type: {kdf_type}"
+        ))),
+    }
+}
+
+/// Split a [Kdf] back into the server's `(Kdf, KdfIterations, KdfMemory, KdfParallelism)` columns.
+pub(crate) fn kdf_columns(kdf: &Kdf) -> (u32, u32, Option<u32>, Option<u32>) {
+    match kdf {
+        Kdf::PBKDF2 { iterations } => (KDF_PBKDF2, iterations.get(), None, None),
+        Kdf::Argon2id {
+            iterations,
+            memory,
+            parallelism,
+        } => (
+            KDF_ARGON2ID,
+            iterations.get(),
+            Some(memory.get()),
+            Some(parallelism.get()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helpers_assign_kinds_where_errors_arise() {
+        let key = rng::make_symmetric_key();
+        let other = rng::make_symmetric_key();
+        let wrapped = wrap_key(&other, &key).unwrap();
+        let kind = |result: Result<SymmetricCryptoKey, SdkError>| result.err().unwrap().kind;
+
+        assert_eq!(kind(parse_key("not base64!")), ErrorKind::InvalidBase64);
+        assert_eq!(
+            kind(parse_key(&STANDARD.encode([0u8; 10]))),
+            ErrorKind::InvalidKeyLength
+        );
+        assert_eq!(kind(unwrap_key(&wrapped, &other)), ErrorKind::MacFailure);
+        assert_eq!(
+            kind(unwrap_key("C:\\Users\\invalid key.txt", &key)),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            make_kdf(7, 1, 0, 0).unwrap_err().kind,
+            ErrorKind::UnsupportedMode
+        );
+    }
+
+    #[test]
+    fn ffi_boundary_turns_panic_into_error_envelope() {
+        let ptr = ffi_boundary(|| panic!("pool index {} is out of bounds", 7));
+        assert_eq!(take_response(ptr).unwrap_err(), "panic");
+        assert_eq!(
+            take_response(ffi_boundary(|| success_response("ok"))).unwrap(),
+            "ok"
+        );
+    }
+
+    #[test]
+    fn envelope_distinguishes_error_lookalike_payload() {
+        let lookalike = r#"{"error": "not really"}"#;
+        assert_eq!(
+            take_response(success_response(lookalike)).unwrap(),
+            lookalike
+        );
+        assert_eq!(
+            take_response(error_response(SdkError::invalid_base64(
+                "Failed to decode base64 key"
+            )))
+            .unwrap_err(),
+            "invalidBase64"
+        );
+    }
 }
@@ -0,0 +1,123 @@
+//! Trusted Device Encryption (TDE) key material for the Seeder.
+//!
+//! A trusted device holds its own symmetric device key and RSA keypair. The server stores the user
+//! key encapsulated to the device public key, the device public key encrypted with the user key (so
+//! the user can rotate every device at once), and the device private key encrypted with the device
+//! key, which only the device itself holds.
+
+use std::ffi::{c_char, CStr};
+
+use crate::crypto_util::{
+    encapsulate_key, error_response, ffi_boundary, parse_key, success_response, SdkError,
+};
+use crate::{pool_material, pool_public_key, rng};
+
+/// Generate the key material a trusted device carries for a user.
+///
+/// The device keypair is drawn from the same pre-generated RSA pool as user keypairs, so seeding many
+/// devices costs no RSA generation.
+///
+/// # Arguments
+/// * `user_key_b64` - Base64-encoded user key of the device's owner
+/// * `pool_index` - Index into the pre-generated RSA keypair pool for the device keypair
+///
+/// # Returns
+/// JSON `{ "deviceKey": <base64>, "encryptedUserKey": <EncString>, "encryptedPublicKey": <EncString>,
+/// "encryptedPrivateKey": <EncString> }`. The last three map onto the `Device` columns of the same
+/// names; `deviceKey` is what the client keeps locally.
+///
+/// # Safety
+/// `user_key_b64` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn generate_device_keys(
+    user_key_b64: *const c_char,
+    pool_index: u32,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(user_key_b64) = CStr::from_ptr(user_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("user_key_b64"));
+        };
+
+        match generate_device_keys_internal(user_key_b64, pool_index) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn generate_device_keys_internal(user_key_b64: &str, pool_index: u32) -> Result<String, SdkError> {
+    let user_key = parse_key(user_key_b64)?;
+    let device_key = rng::make_symmetric_key();
+    let material = pool_material(pool_index);
+
+    let encrypted_user_key = encapsulate_key(&user_key, &pool_public_key(pool_index))?;
+    let encrypted_public_key = rng::encrypt(&material.public_der, &user_key)
+        .map_err(|_| SdkError::crypto_failure("Failed to encrypt device public key"))?;
+    let encrypted_private_key = rng::encrypt(&material.private_der, &device_key)
+        .map_err(|_| SdkError::crypto_failure("Failed to encrypt device private key"))?;
+
+    let result = serde_json::json!({
+        "deviceKey": device_key.to_base64(),
+        "encryptedUserKey": encrypted_user_key,
+        "encryptedPublicKey": encrypted_public_key.to_string(),
+        "encryptedPrivateKey": encrypted_private_key.to_string(),
+    });
+
+    serde_json::to_string(&result)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize device keys"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitwarden_crypto::{
+        EncString, KeyDecryptable, Pkcs8PrivateKeyBytes, PrivateKey, SymmetricCryptoKey,
+        SymmetricKeyAlgorithm, UnsignedSharedKey,
+    };
+
+    #[test]
+    fn generate_device_keys_lets_the_device_recover_the_user_key() {
+        let user_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let user_key_b64: String = user_key.to_base64().into();
+
+        let json = generate_device_keys_internal(&user_key_b64, 7).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // Login with a trusted device: unwrap the device private key with the device key, then
+        // decapsulate the user key with it.
+        let device_key = parse_key(parsed["deviceKey"].as_str().unwrap()).unwrap();
+        let private_enc: EncString = parsed["encryptedPrivateKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let private_der: Vec<u8> = private_enc.decrypt_with_key(&device_key).unwrap();
+        let private_key = PrivateKey::from_der(&Pkcs8PrivateKeyBytes::from(private_der)).unwrap();
+
+        let shared: UnsignedSharedKey = parsed["encryptedUserKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        #[allow(deprecated)]
+        let recovered = shared.decapsulate_key_unsigned(&private_key).unwrap();
+        assert_eq!(<String>::from(recovered.to_base64()), user_key_b64);
+    }
+
+    #[test]
+    fn generate_device_keys_encrypts_public_key_with_user_key() {
+        let user_key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let user_key_b64: String = user_key.to_base64().into();
+
+        let json = generate_device_keys_internal(&user_key_b64, 7).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let public_enc: EncString = parsed["encryptedPublicKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let public_der: Vec<u8> = public_enc.decrypt_with_key(&user_key).unwrap();
+        assert_eq!(public_der, pool_material(7).public_der.as_ref());
+    }
+}
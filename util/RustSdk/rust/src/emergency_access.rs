@@ -0,0 +1,168 @@
+//! Emergency access key exchange for the Seeder.
+//!
+//! Once a grantee accepts an invite and the grantor confirms them, the server stores
+//! `EmergencyAccess.KeyEncrypted`: the grantor's user key encapsulated to the grantee's public key.
+//! A takeover has the grantee unwrap their own private key, decapsulate the grantor's user key and
+//! set a new master password for the grantor.
+
+use std::ffi::{c_char, CStr};
+
+use crate::account_recovery::recover_account_internal;
+use crate::crypto_util::{
+    encapsulate_key, error_response, ffi_boundary, make_kdf, parse_key, parse_public_key,
+    success_response, SdkError,
+};
+
+/// Confirm an emergency access grant: encapsulate the grantor's user key to the grantee public key.
+///
+/// # Arguments
+/// * `grantor_user_key_b64` - Base64-encoded user key of the grantor
+/// * `grantee_public_key_b64` - Base64-encoded SPKI public key of the grantee
+///
+/// # Returns
+/// The `EmergencyAccess.KeyEncrypted` EncString, in format "4.{data}"
+///
+/// # Safety
+/// Both pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn generate_emergency_access_key(
+    grantor_user_key_b64: *const c_char,
+    grantee_public_key_b64: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(grantor_user_key_b64) = CStr::from_ptr(grantor_user_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("grantor_user_key_b64"));
+        };
+        let Ok(grantee_public_key_b64) = CStr::from_ptr(grantee_public_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("grantee_public_key_b64"));
+        };
+
+        let result = parse_key(grantor_user_key_b64).and_then(|grantor_user_key| {
+            encapsulate_key(
+                &grantor_user_key,
+                &parse_public_key(grantee_public_key_b64)?,
+            )
+        });
+
+        match result {
+            Ok(key_encrypted) => success_response(&key_encrypted),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Simulate a grantee taking over the grantor's account under a new master password.
+///
+/// # Arguments
+/// * `key_encrypted` - The `EmergencyAccess.KeyEncrypted` of the grant
+/// * `grantee_private_key` - The grantee's `PrivateKey` EncString, wrapped by the grantee's user key
+/// * `grantee_user_key_b64` - Base64-encoded user key of the grantee
+/// * `grantor_email` - The grantor's email, used as the new master key salt
+/// * `new_password` - The new master password the grantee sets
+/// * `kdf_type`, `kdf_iterations`, `kdf_memory`, `kdf_parallelism` - The grantor's KDF settings
+///
+/// # Returns
+/// The same JSON as `recover_account`: the grantor's unchanged user key, now wrapped by the master
+/// key of `new_password`, with the new master password hash and KDF columns.
+///
+/// # Safety
+/// All pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn take_over_emergency_access(
+    key_encrypted: *const c_char,
+    grantee_private_key: *const c_char,
+    grantee_user_key_b64: *const c_char,
+    grantor_email: *const c_char,
+    new_password: *const c_char,
+    kdf_type: u32,
+    kdf_iterations: u32,
+    kdf_memory: u32,
+    kdf_parallelism: u32,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(key_encrypted) = CStr::from_ptr(key_encrypted).to_str() else {
+            return error_response(SdkError::invalid_utf8("key_encrypted"));
+        };
+        let Ok(grantee_private_key) = CStr::from_ptr(grantee_private_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("grantee_private_key"));
+        };
+        let Ok(grantee_user_key_b64) = CStr::from_ptr(grantee_user_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("grantee_user_key_b64"));
+        };
+        let Ok(grantor_email) = CStr::from_ptr(grantor_email).to_str() else {
+            return error_response(SdkError::invalid_utf8("grantor_email"));
+        };
+        let Ok(new_password) = CStr::from_ptr(new_password).to_str() else {
+            return error_response(SdkError::invalid_utf8("new_password"));
+        };
+
+        let result =
+            make_kdf(kdf_type, kdf_iterations, kdf_memory, kdf_parallelism).and_then(|kdf| {
+                recover_account_internal(
+                    key_encrypted,
+                    grantee_private_key,
+                    grantee_user_key_b64,
+                    grantor_email,
+                    new_password,
+                    &kdf,
+                )
+            });
+
+        match result {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::{call_ffi_string, make_test_key, KDF_ARGON2ID};
+    use crate::keypair;
+    use bitwarden_crypto::{EncString, MasterKey};
+
+    #[test]
+    fn takeover_recovers_grantor_user_key_under_new_password() {
+        let grantor_user_key = make_test_key();
+        let grantor_user_key_b64: String = grantor_user_key.to_base64().into();
+        let grantee_user_key = make_test_key();
+        let grantee_user_key_b64: String = grantee_user_key.to_base64().into();
+        let grantee_keypair = keypair(&grantee_user_key, 13).unwrap();
+
+        let key_encrypted = call_ffi_string(
+            generate_emergency_access_key,
+            &grantor_user_key_b64,
+            &grantee_keypair.public.to_string(),
+        )
+        .unwrap();
+        assert!(
+            key_encrypted.starts_with("4."),
+            "Expected an RSA-OAEP EncString, got: {key_encrypted}"
+        );
+
+        let kdf = make_kdf(KDF_ARGON2ID, 3, 64, 4).unwrap();
+        let json = recover_account_internal(
+            &key_encrypted,
+            &grantee_keypair.private.to_string(),
+            &grantee_user_key_b64,
+            "grantor@example.com",
+            "taken-over-password",
+            &kdf,
+        )
+        .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed["key"].as_str().unwrap(), grantor_user_key_b64);
+        assert_eq!(parsed["kdf"].as_u64().unwrap(), 1);
+        let master_key =
+            MasterKey::derive("taken-over-password", "grantor@example.com", &kdf).unwrap();
+        let encrypted: EncString = parsed["encryptedUserKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let unwrapped = master_key.decrypt_user_key(encrypted).unwrap();
+        assert_eq!(<String>::from(unwrapped.to_base64()), grantor_user_key_b64);
+    }
+}
@@ -0,0 +1,117 @@
+//! Fingerprint phrases for key verification.
+//!
+//! Clients show a five-word phrase from the EFF long wordlist when a user or admin confirms someone's
+//! public key: admin confirmation of organization members, emergency access and account recovery
+//! enrollment. The phrase is derived from the public key and a fingerprint material, usually the
+//! user's or organization's ID, through `bitwarden_crypto`'s `fingerprint`, so seeded data can carry
+//! exactly the phrase a client would display.
+
+use std::ffi::{c_char, CStr};
+
+use bitwarden_crypto::fingerprint;
+
+use crate::crypto_util::{
+    error_response, ffi_boundary, parse_public_key, success_response, SdkError,
+};
+
+/// Compute the fingerprint phrase a client shows for a public key.
+///
+/// # Arguments
+/// * `fingerprint_material` - The material the phrase is bound to, e.g. the user's ID
+/// * `public_key_b64` - Base64-encoded SPKI public key
+///
+/// # Returns
+/// The phrase: five words joined with `-`.
+///
+/// # Safety
+/// Both pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn fingerprint_phrase(
+    fingerprint_material: *const c_char,
+    public_key_b64: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(fingerprint_material) = CStr::from_ptr(fingerprint_material).to_str() else {
+            return error_response(SdkError::invalid_utf8("fingerprint_material"));
+        };
+        let Ok(public_key_b64) = CStr::from_ptr(public_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("public_key_b64"));
+        };
+
+        match fingerprint_phrase_internal(fingerprint_material, public_key_b64) {
+            Ok(phrase) => success_response(&phrase),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn fingerprint_phrase_internal(
+    fingerprint_material: &str,
+    public_key_b64: &str,
+) -> Result<String, SdkError> {
+    let public_key = parse_public_key(public_key_b64)?
+        .to_der()
+        .map_err(|_| SdkError::crypto_failure("Failed to encode public key DER"))?;
+
+    fingerprint(fingerprint_material, &public_key)
+        .map_err(|_| SdkError::crypto_failure("Failed to compute fingerprint phrase"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::ErrorKind;
+    use crate::pool_material;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bitwarden_crypto::EFF_LONG_WORD_LIST;
+
+    /// SPKI of compiled-in pool key 0.
+    const POOL_KEY_0_B64: &str = "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEA2bOrlQtyfqzydFagzWfDFbskocb/8jWFUIJ0Rgq5BTXBtNOO8oR328B4tR5hLmATkMP5ws4ULXbOdEuIy9mEcEzl3FnCzx2+9AdK28JQYjJpUtRrpRMeg4q/nikkLucw5sy3u0KUgJVwDW6wJPD2yLkAvyDgJ4HZ/rrBMFr16sTWV/mvBXIFujAPaPa0/O7GVnvL13g42f9XkfvE+kYewaAc/VSyNEC7kaxaNLYe5MkBzplAX/zaiY8bkAXNn7QjvshpG54JT+Um3kqVCBK8IcvQN1zWI2czxd5iRIQOS3ga7nk3fK869DqyGceR3HgNEqH+n2hgOLg1a1+HaSxG2QIDAQAB";
+
+    #[test]
+    fn fingerprint_phrase_matches_known_answer() {
+        assert_eq!(
+            STANDARD.encode(pool_material(0).public_der.as_ref()),
+            POOL_KEY_0_B64
+        );
+
+        // Computed independently as clients do: HKDF-Expand(SHA-256(SPKI), info = material, 32 bytes),
+        // read big-endian and taken apart into five base-7776 digits, least significant first.
+        let expected = [5433, 5482, 875, 6624, 2187]
+            .map(|i| EFF_LONG_WORD_LIST[i])
+            .join("-");
+        assert_eq!(
+            fingerprint_phrase_internal("a09726a0-9590-49d1-a5f5-afe300b6a515", POOL_KEY_0_B64)
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn fingerprint_phrase_is_five_words_bound_to_material_and_key() {
+        let user_id = "a09726a0-9590-49d1-a5f5-afe300b6a515";
+        let key_b64 = |i: u32| STANDARD.encode(pool_material(i).public_der.as_ref());
+
+        let phrase = fingerprint_phrase_internal(user_id, &key_b64(0)).unwrap();
+        assert_eq!(phrase.split('-').count(), 5, "got: {phrase}");
+        assert_eq!(
+            fingerprint_phrase_internal(user_id, &key_b64(0)).unwrap(),
+            phrase
+        );
+        assert_ne!(
+            fingerprint_phrase_internal("another-user", &key_b64(0)).unwrap(),
+            phrase
+        );
+        assert_ne!(
+            fingerprint_phrase_internal(user_id, &key_b64(1)).unwrap(),
+            phrase
+        );
+
+        assert_eq!(
+            fingerprint_phrase_internal(user_id, "not base64!")
+                .unwrap_err()
+                .kind,
+            ErrorKind::InvalidBase64
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Registered symmetric keys, addressed by opaque handles.
+//!
+//! The Seeder reuses one organization key for thousands of calls. `register_key` parses it once and
+//! returns a handle that the `*_with_handle` functions accept in its place, so the key is neither
+//! base64-decoded again nor sent across the FFI boundary on every call. A handle stays valid until
+//! `release_key` and is never reused.
+
+use std::{
+    collections::HashMap,
+    ffi::{c_char, CStr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, LazyLock, PoisonError, RwLock,
+    },
+};
+
+use bitwarden_crypto::SymmetricCryptoKey;
+
+use crate::crypto_util::{error_response, ffi_boundary, parse_key, success_response, SdkError};
+
+static KEYS: LazyLock<RwLock<HashMap<u64, Arc<SymmetricCryptoKey>>>> =
+    LazyLock::new(Default::default);
+
+/// Handle 0 is never issued, so callers can use it as "no key".
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/// Parse a symmetric key once and register it for use by handle.
+///
+/// # Arguments
+/// * `key_b64` - Base64-encoded symmetric key
+///
+/// # Returns
+/// The handle, as a decimal string.
+///
+/// # Safety
+/// `key_b64` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn register_key(key_b64: *const c_char) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(key_b64) = CStr::from_ptr(key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("key_b64"));
+        };
+
+        match parse_key(key_b64) {
+            Ok(key) => success_response(&register(key).to_string()),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Release a handle returned by `register_key`, dropping its key.
+///
+/// # Returns
+/// Whether the handle was registered.
+#[no_mangle]
+pub unsafe extern "C" fn release_key(handle: u64) -> bool {
+    KEYS.write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&handle)
+        .is_some()
+}
+
+fn register(key: SymmetricCryptoKey) -> u64 {
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    KEYS.write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(handle, Arc::new(key));
+    handle
+}
+
+/// Look up a registered key.
+pub(crate) fn resolve_key(handle: u64) -> Result<Arc<SymmetricCryptoKey>, SdkError> {
+    KEYS.read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&handle)
+        .cloned()
+        .ok_or_else(|| SdkError::invalid_input(format!("Invalid key handle: {handle}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::take_response;
+    use bitwarden_crypto::SymmetricKeyAlgorithm;
+    use std::ffi::CString;
+
+    #[test]
+    fn registered_key_resolves_until_released() {
+        let key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let key_b64: String = key.to_base64().into();
+
+        let key_cstr = CString::new(key_b64.clone()).unwrap();
+        let handle: u64 = take_response(unsafe { register_key(key_cstr.as_ptr()) })
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        let resolved = resolve_key(handle).unwrap();
+        assert_eq!(<String>::from(resolved.to_base64()), key_b64);
+
+        assert!(unsafe { release_key(handle) });
+        assert!(!unsafe { release_key(handle) });
+        assert_eq!(
+            resolve_key(handle).unwrap_err().message,
+            format!("Invalid key handle: {handle}")
+        );
+    }
+}
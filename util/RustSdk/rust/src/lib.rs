@@ -1,25 +1,46 @@
 #![allow(clippy::missing_safety_doc)]
 
+mod account_recovery;
+mod account_v2;
 mod attachment;
+mod attachment_stream;
+mod auth_request;
 mod cipher;
 mod crypto_util;
+mod device;
+mod emergency_access;
+mod fingerprint;
+mod key_handle;
+mod master_key_cache;
 mod provider;
+mod rng;
 mod rsa_keys;
+mod rsa_pool;
+mod secrets_manager;
+mod send;
+mod vault_check;
 
 use std::{
     ffi::{c_char, CStr, CString},
-    num::NonZeroU32,
-    sync::LazyLock,
+    ops::Deref,
+    sync::{Arc, LazyLock},
 };
 
 use base64::{engine::general_purpose::STANDARD, Engine};
+use rayon::prelude::*;
+use serde::Deserialize;
 
 use bitwarden_crypto::{
-    BitwardenLegacyKeyBytes, HashPurpose, Kdf, KeyEncryptable, MasterKey, Pkcs8PrivateKeyBytes,
-    PrivateKey, PublicKey, RsaKeyPair, SpkiPublicKeyBytes, SymmetricCryptoKey,
-    SymmetricKeyAlgorithm, UnsignedSharedKey, UserKey,
+    Kdf, Pkcs8PrivateKeyBytes, PrivateKey, PublicKey, RsaKeyPair, SpkiPublicKeyBytes,
+    SymmetricCryptoKey, UserKey,
 };
 
+use crate::crypto_util::{
+    encapsulate_key, error_response, ffi_boundary, kdf_columns, make_kdf, parse_key,
+    parse_public_key, stretch_master_key, success_response, wrap_key, SdkError, KDF_PBKDF2,
+};
+use crate::master_key_cache::derive_master_key;
+
 #[no_mangle]
 pub unsafe extern "C" fn generate_user_keys(
     email: *const c_char,
@@ -27,42 +48,173 @@ pub unsafe extern "C" fn generate_user_keys(
     kdf_iterations: u32,
     pool_index: u32,
 ) -> *const c_char {
-    let email = CStr::from_ptr(email).to_str().unwrap();
-    let password = CStr::from_ptr(password).to_str().unwrap();
+    ffi_boundary(|| {
+        generate_user_keys_with_kdf(
+            email,
+            password,
+            KDF_PBKDF2,
+            kdf_iterations,
+            0,
+            0,
+            pool_index,
+        )
+    })
+}
 
-    let iterations = match NonZeroU32::new(kdf_iterations) {
-        Some(iter) => iter,
-        None => return error_response("kdf_iterations must be non-zero"),
-    };
+/// Generate a user's key material with an explicit KDF, so Seeder users can be Argon2id accounts.
+///
+/// # Arguments
+/// * `email` - The user's email, used as the master key salt
+/// * `password` - The user's master password
+/// * `kdf_type` - 0 = PBKDF2-SHA256, 1 = Argon2id (matches the server's `KdfType`)
+/// * `kdf_iterations` - KDF iterations; must be non-zero
+/// * `kdf_memory` - Argon2id memory in MiB; ignored for PBKDF2
+/// * `kdf_parallelism` - Argon2id parallelism; ignored for PBKDF2
+/// * `pool_index` - Index into the pre-generated RSA keypair pool
+///
+/// # Returns
+/// The same JSON as `generate_user_keys`; both include the `kdf`, `kdfIterations`, `kdfMemory` and
+/// `kdfParallelism` the master key was derived with (memory and parallelism are null for PBKDF2).
+///
+/// # Safety
+/// `email` and `password` must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn generate_user_keys_with_kdf(
+    email: *const c_char,
+    password: *const c_char,
+    kdf_type: u32,
+    kdf_iterations: u32,
+    kdf_memory: u32,
+    kdf_parallelism: u32,
+    pool_index: u32,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(email) = CStr::from_ptr(email).to_str() else {
+            return error_response(SdkError::invalid_utf8("email"));
+        };
+        let Ok(password) = CStr::from_ptr(password).to_str() else {
+            return error_response(SdkError::invalid_utf8("password"));
+        };
+
+        let result = make_kdf(kdf_type, kdf_iterations, kdf_memory, kdf_parallelism)
+            .and_then(|kdf| user_keys(email, password, &kdf, pool_index));
+
+        match result {
+            Ok(json) => success_response(&json.to_string()),
+            Err(err) => error_response(err),
+        }
+    })
+}
 
-    let kdf = Kdf::PBKDF2 { iterations };
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkUser {
+    email: String,
+    password: String,
+    #[serde(default)]
+    kdf: u32,
+    kdf_iterations: u32,
+    #[serde(default)]
+    kdf_memory: Option<u32>,
+    #[serde(default)]
+    kdf_parallelism: Option<u32>,
+    #[serde(default)]
+    pool_index: u32,
+}
 
-    let master_key = MasterKey::derive(password, email, &kdf).unwrap();
+/// Generate many users' key material in one call, running their master key derivations in parallel.
+///
+/// Derivations land in the master key cache. When the RNG is seeded, the user keys are then generated
+/// in input order, so output stays reproducible; otherwise that step runs in parallel too.
+///
+/// # Arguments
+/// * `users_json` - `[{ "email", "password", "kdf", "kdfIterations", "kdfMemory", "kdfParallelism",
+///   "poolIndex" }]`, with the arguments of `generate_user_keys_with_kdf`. `kdf` and
+///   `poolIndex` default to 0; `kdfMemory` and `kdfParallelism` may be omitted or null for PBKDF2.
+///
+/// # Returns
+/// A JSON array of `generate_user_keys_with_kdf` results, in input order.
+///
+/// # Safety
+/// `users_json` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn generate_user_keys_bulk(users_json: *const c_char) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(users_json) = CStr::from_ptr(users_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("users_json"));
+        };
+
+        match generate_user_keys_bulk_internal(users_json) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
 
-    let master_password_hash =
-        master_key.derive_master_key_hash(password.as_bytes(), HashPurpose::ServerAuthorization);
+fn generate_user_keys_bulk_internal(users_json: &str) -> Result<String, SdkError> {
+    let users: Vec<BulkUser> = serde_json::from_str(users_json)
+        .map_err(|e| SdkError::invalid_input(format!("Invalid bulk user JSON: {e}")))?;
+
+    let kdfs = users
+        .par_iter()
+        .enumerate()
+        .map(|(index, user)| {
+            let kdf = make_kdf(
+                user.kdf,
+                user.kdf_iterations,
+                user.kdf_memory.unwrap_or(0),
+                user.kdf_parallelism.unwrap_or(0),
+            )
+            .and_then(|kdf| derive_master_key(&user.email, &user.password, &kdf).map(|_| kdf))
+            .map_err(|err| SdkError::new(err.kind, format!("User {index}: {err}")))?;
+            Ok::<_, SdkError>(kdf)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let (user_key, encrypted_user_key) = master_key.make_user_key().unwrap();
+    let generate = |(index, (user, kdf)): (usize, (&BulkUser, &Kdf))| {
+        user_keys(&user.email, &user.password, kdf, user.pool_index)
+            .map_err(|err| SdkError::new(err.kind, format!("User {index}: {err}")))
+    };
 
-    let keypair = keypair(&user_key.0, pool_index);
+    let generated: Result<Vec<serde_json::Value>, SdkError> = if rng::is_seeded() {
+        users.iter().zip(&kdfs).enumerate().map(generate).collect()
+    } else {
+        users
+            .par_iter()
+            .zip(&kdfs)
+            .enumerate()
+            .map(generate)
+            .collect()
+    };
 
-    let json = serde_json::json!({
-        "masterPasswordHash": master_password_hash,
-        "key": user_key.0.to_base64(),
-        "encryptedUserKey": encrypted_user_key.to_string(),
-        "publicKey": keypair.public.to_string(),
-        "privateKey": keypair.private.to_string(),
-    })
-    .to_string();
+    serde_json::to_string(&generated?)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize result JSON"))
+}
 
-    let result = CString::new(json).unwrap();
+/// The `generate_user_keys_with_kdf` JSON for one user, with the master key from the cache.
+fn user_keys(
+    email: &str,
+    password: &str,
+    kdf: &Kdf,
+    pool_index: u32,
+) -> Result<serde_json::Value, SdkError> {
+    let derived = derive_master_key(email, password, kdf)?;
+    let (user_key, encrypted_user_key) = rng::make_user_key(&derived.master_key)?;
+    let keypair = keypair(&user_key, pool_index)?;
 
-    result.into_raw()
-}
+    let (kdf_type, kdf_iterations, kdf_memory, kdf_parallelism) = kdf_columns(kdf);
 
-fn error_response(message: &str) -> *const c_char {
-    let json = serde_json::json!({ "error": message }).to_string();
-    CString::new(json).unwrap().into_raw()
+    Ok(serde_json::json!({
+        "masterPasswordHash": derived.master_password_hash,
+        "key": user_key.to_base64(),
+        "encryptedUserKey": encrypted_user_key.to_string(),
+        "publicKey": keypair.public.to_string(),
+        "privateKey": keypair.private.to_string(),
+        "kdf": kdf_type,
+        "kdfIterations": kdf_iterations,
+        "kdfMemory": kdf_memory,
+        "kdfParallelism": kdf_parallelism,
+    }))
 }
 
 struct CachedRsaMaterial {
@@ -70,50 +222,168 @@ struct CachedRsaMaterial {
     public_der: SpkiPublicKeyBytes,
 }
 
+impl CachedRsaMaterial {
+    fn from_private_key(pk: &PrivateKey) -> Result<Self, SdkError> {
+        Ok(CachedRsaMaterial {
+            public_der: pk
+                .to_public_key()
+                .to_der()
+                .map_err(|_| SdkError::crypto_failure("Public DER conversion failed"))?,
+            private_der: pk
+                .to_der()
+                .map_err(|_| SdkError::crypto_failure("Private DER conversion failed"))?,
+        })
+    }
+}
+
 /// Pre-parsed DER keypairs from `rsa_keys.rs`, lazily initialized on first access.
-static RSA_POOL: LazyLock<Vec<CachedRsaMaterial>> = LazyLock::new(|| {
+static RSA_POOL: LazyLock<Arc<[CachedRsaMaterial]>> = LazyLock::new(|| {
     rsa_keys::TEST_FAKE_RSA_KEYS
         .iter()
         .map(|pem| {
             let pk = PrivateKey::from_pem(pem).expect("seeded RSA PEM must be valid");
-            CachedRsaMaterial {
-                public_der: pk
-                    .to_public_key()
-                    .to_der()
-                    .expect("public DER conversion failed"),
-                private_der: pk.to_der().expect("private DER conversion failed"),
-            }
+            CachedRsaMaterial::from_private_key(&pk).expect("seeded RSA key must convert to DER")
         })
         .collect()
 });
 
-fn keypair(key: &SymmetricCryptoKey, pool_index: u32) -> RsaKeyPair {
-    let pool = &*RSA_POOL;
-    let material = &pool[pool_index as usize % pool.len()];
+/// The pool `pool_index` selects from: a pool loaded by `rsa_pool`, or else `RSA_POOL`.
+fn active_rsa_pool() -> Arc<[CachedRsaMaterial]> {
+    rsa_pool::configured().unwrap_or_else(|| RSA_POOL.clone())
+}
 
-    RsaKeyPair {
-        private: material.private_der.encrypt_with_key(key).unwrap(),
-        public: material.public_der.clone().into(),
+/// An entry of the active RSA pool. It keeps its pool alive, so a pool replaced through `rsa_pool` is
+/// freed once the last entry taken from it is dropped.
+struct PoolEntry {
+    pool: Arc<[CachedRsaMaterial]>,
+    index: usize,
+}
+
+impl Deref for PoolEntry {
+    type Target = CachedRsaMaterial;
+
+    fn deref(&self) -> &CachedRsaMaterial {
+        &self.pool[self.index]
     }
 }
 
+fn pool_material(pool_index: u32) -> PoolEntry {
+    let pool = active_rsa_pool();
+    let index = pool_index as usize % pool.len();
+    PoolEntry { pool, index }
+}
+
+/// The pool entry's public key, for callers that need more than its DER (e.g. to sign it).
+fn pool_public_key(pool_index: u32) -> PublicKey {
+    PublicKey::from_der(&pool_material(pool_index).public_der)
+        .expect("pooled public DER must be valid")
+}
+
+fn keypair(key: &SymmetricCryptoKey, pool_index: u32) -> Result<RsaKeyPair, SdkError> {
+    let material = pool_material(pool_index);
+
+    Ok(RsaKeyPair {
+        private: rng::encrypt(&material.private_der, key)
+            .map_err(|_| SdkError::crypto_failure("Failed to encrypt private key"))?,
+        public: material.public_der.clone().into(),
+    })
+}
+
+/// Generate the key material of a Key Connector user, who has no master password.
+///
+/// The master key is random rather than derived; a local key-connector stand-in stores it and hands
+/// it back on unlock, and it wraps the user key exactly like a password-derived master key would.
+///
+/// # Arguments
+/// * `pool_index` - Index into the pre-generated RSA keypair pool
+///
+/// # Returns
+/// JSON `{ "masterKey": <base64>, "key": <base64>, "encryptedUserKey": <EncString>,
+/// "publicKey": <base64>, "privateKey": <EncString> }`
 #[no_mangle]
-pub unsafe extern "C" fn generate_organization_keys() -> *const c_char {
-    let key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+pub unsafe extern "C" fn generate_key_connector_user_keys(pool_index: u32) -> *const c_char {
+    ffi_boundary(
+        || match generate_key_connector_user_keys_internal(pool_index) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        },
+    )
+}
 
-    let key = UserKey::new(key);
-    let keypair = key.make_key_pair().expect("Failed to generate key pair");
+fn generate_key_connector_user_keys_internal(pool_index: u32) -> Result<String, SdkError> {
+    let master_key: [u8; 32] = rng::random_bytes();
+    let stretched_master_key = stretch_master_key(&master_key)?;
+
+    let user_key = rng::make_symmetric_key();
+    let encrypted_user_key = wrap_key(&user_key, &stretched_master_key)?;
+
+    let keypair = keypair(&user_key, pool_index)?;
 
     let json = serde_json::json!({
-        "key": key.0.to_base64(),
+        "masterKey": STANDARD.encode(master_key),
+        "key": user_key.to_base64(),
+        "encryptedUserKey": encrypted_user_key,
         "publicKey": keypair.public.to_string(),
         "privateKey": keypair.private.to_string(),
+    });
+
+    serde_json::to_string(&json)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize key connector user keys"))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn generate_organization_keys() -> *const c_char {
+    ffi_boundary(|| {
+        let key = UserKey::new(rng::make_symmetric_key());
+
+        // When seeded, draw the keypair from the pool so it is reproducible rather than freshly generated.
+        let keypair = match rng::seeded_index(active_rsa_pool().len()) {
+            Some(index) => keypair(&key.0, index as u32),
+            None => key
+                .make_key_pair()
+                .map_err(|_| SdkError::crypto_failure("Failed to generate key pair")),
+        };
+        match keypair {
+            Ok(keypair) => success_response(&organization_keys_json(&key.0, &keypair)),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Generate an organization's symmetric key, drawing its RSA keypair from the pool instead of generating
+/// one, so seeding thousands of organizations is not bound on prime generation.
+///
+/// Organizations share the pool with users; offset `pool_index` past the users' indexes to give an
+/// organization a keypair no user has.
+///
+/// # Arguments
+/// * `pool_index` - Index into the RSA keypair pool
+///
+/// # Returns
+/// The `generate_organization_keys` JSON, with the pooled private key wrapped by the new organization key.
+#[no_mangle]
+pub unsafe extern "C" fn generate_organization_keys_from_pool(pool_index: u32) -> *const c_char {
+    ffi_boundary(|| match pooled_organization_keys(pool_index) {
+        Ok(json) => success_response(&json),
+        Err(err) => error_response(err),
     })
-    .to_string();
+}
 
-    let result = CString::new(json).unwrap();
+/// A fresh symmetric key plus the pool entry's keypair, its private key wrapped by that symmetric key.
+/// Organizations and providers store their keys in this shape.
+fn pooled_organization_keys(pool_index: u32) -> Result<String, SdkError> {
+    let key = rng::make_symmetric_key();
+    let keypair = keypair(&key, pool_index)?;
+    Ok(organization_keys_json(&key, &keypair))
+}
 
-    result.into_raw()
+fn organization_keys_json(key: &SymmetricCryptoKey, keypair: &RsaKeyPair) -> String {
+    serde_json::json!({
+        "key": key.to_base64(),
+        "publicKey": keypair.public.to_string(),
+        "privateKey": keypair.private.to_string(),
+    })
+    .to_string()
 }
 
 #[no_mangle]
@@ -121,30 +391,26 @@ pub unsafe extern "C" fn generate_user_organization_key(
     user_public_key: *const c_char,
     organization_key: *const c_char,
 ) -> *const c_char {
-    let user_public_key = CStr::from_ptr(user_public_key).to_str().unwrap().to_owned();
-    let organization_key = CStr::from_ptr(organization_key)
-        .to_str()
-        .unwrap()
-        .to_owned();
-
-    let user_public_key = STANDARD.decode(user_public_key).unwrap();
-    let organization_key = STANDARD.decode(organization_key).unwrap();
-
-    let encapsulation_key =
-        PublicKey::from_der(&SpkiPublicKeyBytes::from(user_public_key)).unwrap();
-
-    // The Seeder uses unsigned key encapsulation for test data generation.
-    // When the SDK removes this deprecated API, migrate to signed encapsulation.
-    #[allow(deprecated)]
-    let encrypted_key = UnsignedSharedKey::encapsulate_key_unsigned(
-        &SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(organization_key)).unwrap(),
-        &encapsulation_key,
-    )
-    .unwrap();
-
-    let result = CString::new(encrypted_key.to_string()).unwrap();
+    ffi_boundary(|| {
+        let Ok(user_public_key) = CStr::from_ptr(user_public_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("user_public_key"));
+        };
+        let Ok(organization_key) = CStr::from_ptr(organization_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("organization_key"));
+        };
+
+        match encapsulate_shared_key(user_public_key, organization_key) {
+            Ok(encapsulated) => success_response(&encapsulated),
+            Err(err) => error_response(err),
+        }
+    })
+}
 
-    result.into_raw()
+/// Encapsulate a shared symmetric key (an organization's or a provider's) to a member's public key,
+/// producing the key column of their confirmed membership.
+fn encapsulate_shared_key(user_public_key: &str, shared_key_b64: &str) -> Result<String, SdkError> {
+    let public_key = parse_public_key(user_public_key)?;
+    encapsulate_key(&parse_key(shared_key_b64)?, &public_key)
 }
 
 /// # Safety
@@ -158,14 +424,62 @@ pub unsafe extern "C" fn free_c_string(str: *mut c_char) {
     }
 }
 
+/// Bytes owned by Rust and handed to the caller as a pointer and length, for payloads too large to
+/// send through base64 and JSON. Release with `free_byte_buffer`.
+#[repr(C)]
+pub struct ByteBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl ByteBuffer {
+    pub(crate) fn empty() -> Self {
+        ByteBuffer {
+            data: std::ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    pub(crate) fn from_vec(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        ByteBuffer { data, len }
+    }
+}
+
+/// # Safety
+///
+/// `buffer` must have been returned by this library and must not have already been freed. A buffer
+/// with a null `data` pointer is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn free_byte_buffer(buffer: ByteBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::{collections::HashSet, ffi::CString};
 
-    use bitwarden_crypto::{SymmetricCryptoKey, SymmetricKeyAlgorithm};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bitwarden_crypto::{
+        EncString, KeyDecryptable, MasterKey, SymmetricCryptoKey, SymmetricKeyAlgorithm,
+    };
 
     use super::RSA_POOL;
-    use crate::keypair;
+    use crate::crypto_util::{
+        kdf_columns, make_kdf, parse_key, stretch_master_key, take_response, unwrap_key, SdkError,
+        KDF_ARGON2ID, KDF_PBKDF2,
+    };
+    use crate::{
+        generate_key_connector_user_keys_internal, generate_user_keys_bulk_internal,
+        generate_user_keys_with_kdf, generate_user_organization_key, keypair, pool_material,
+        pooled_organization_keys,
+    };
 
     #[test]
     fn rsa_pool_initializes_all_entries() {
@@ -190,8 +504,8 @@ mod tests {
     #[test]
     fn keypair_different_indices_produce_different_public_keys() {
         let key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
-        let kp0 = keypair(&key, 0);
-        let kp1 = keypair(&key, 1);
+        let kp0 = keypair(&key, 0).unwrap();
+        let kp1 = keypair(&key, 1).unwrap();
         assert_ne!(
             kp0.public.to_string(),
             kp1.public.to_string(),
@@ -202,8 +516,8 @@ mod tests {
     #[test]
     fn keypair_same_index_produces_same_public_key() {
         let key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
-        let kp_a = keypair(&key, 42);
-        let kp_b = keypair(&key, 42);
+        let kp_a = keypair(&key, 42).unwrap();
+        let kp_b = keypair(&key, 42).unwrap();
         assert_eq!(
             kp_a.public.to_string(),
             kp_b.public.to_string(),
@@ -214,12 +528,153 @@ mod tests {
     #[test]
     fn keypair_index_wraps_at_pool_boundary() {
         let key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
-        let kp_zero = keypair(&key, 0);
-        let kp_wrapped = keypair(&key, 100);
+        let kp_zero = keypair(&key, 0).unwrap();
+        let kp_wrapped = keypair(&key, 100).unwrap();
         assert_eq!(
             kp_zero.public.to_string(),
             kp_wrapped.public.to_string(),
             "index 100 should wrap to index 0"
         );
     }
+
+    #[test]
+    fn pooled_organization_keys_wrap_the_pool_private_key() {
+        let json: serde_json::Value =
+            serde_json::from_str(&pooled_organization_keys(17).unwrap()).unwrap();
+
+        assert_eq!(
+            json["publicKey"].as_str().unwrap(),
+            STANDARD.encode(pool_material(17).public_der.as_ref())
+        );
+        let key = parse_key(json["key"].as_str().unwrap()).unwrap();
+        let private_key: EncString = json["privateKey"].as_str().unwrap().parse().unwrap();
+        let private_der: Vec<u8> = private_key.decrypt_with_key(&key).unwrap();
+        assert_eq!(private_der, pool_material(17).private_der.as_ref());
+    }
+
+    #[test]
+    fn generate_user_organization_key_rejects_malformed_input() {
+        let bad_public_key = CString::new("not base64!").unwrap();
+        let organization_key = CString::new("also not base64!").unwrap();
+
+        let ptr = unsafe {
+            generate_user_organization_key(bad_public_key.as_ptr(), organization_key.as_ptr())
+        };
+        assert_eq!(take_response(ptr).unwrap_err(), "invalidBase64");
+    }
+
+    #[test]
+    fn generate_user_keys_with_kdf_derives_argon2id_master_key() {
+        let email = CString::new("argon2@example.com").unwrap();
+        let password = CString::new("asdfasdfasdf").unwrap();
+
+        let ptr = unsafe {
+            generate_user_keys_with_kdf(
+                email.as_ptr(),
+                password.as_ptr(),
+                KDF_ARGON2ID,
+                3,
+                64,
+                4,
+                0,
+            )
+        };
+        let result = take_response(ptr).unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["kdf"].as_u64().unwrap(), 1);
+        assert_eq!(parsed["kdfIterations"].as_u64().unwrap(), 3);
+        assert_eq!(parsed["kdfMemory"].as_u64().unwrap(), 64);
+        assert_eq!(parsed["kdfParallelism"].as_u64().unwrap(), 4);
+
+        // The encrypted user key must unwrap with the Argon2id-derived master key.
+        let kdf = make_kdf(KDF_ARGON2ID, 3, 64, 4).unwrap();
+        let master_key = MasterKey::derive("asdfasdfasdf", "argon2@example.com", &kdf).unwrap();
+        let encrypted: EncString = parsed["encryptedUserKey"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let user_key = master_key.decrypt_user_key(encrypted).unwrap();
+        assert_eq!(
+            <String>::from(user_key.to_base64()),
+            parsed["key"].as_str().unwrap()
+        );
+    }
+
+    #[test]
+    fn generate_user_keys_bulk_unwraps_with_each_users_master_key() {
+        let users = serde_json::json!([
+            { "email": "bulk0@example.com", "password": "asdfasdfasdf", "kdfIterations": 5_000 },
+            { "email": "bulk1@example.com", "password": "asdfasdfasdf", "kdfIterations": 5_000,
+              "poolIndex": 3 },
+        ]);
+
+        let out = generate_user_keys_bulk_internal(&users.to_string()).unwrap();
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(
+            parsed[1]["publicKey"].as_str().unwrap(),
+            STANDARD.encode(pool_material(3).public_der.as_ref())
+        );
+
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        for (index, user) in parsed.iter().enumerate() {
+            let email = format!("bulk{index}@example.com");
+            let master_key = MasterKey::derive("asdfasdfasdf", &email, &kdf).unwrap();
+            let encrypted: EncString = user["encryptedUserKey"].as_str().unwrap().parse().unwrap();
+            let user_key = master_key.decrypt_user_key(encrypted).unwrap();
+            assert_eq!(
+                <String>::from(user_key.to_base64()),
+                user["key"].as_str().unwrap()
+            );
+        }
+
+        let bad = serde_json::json!([
+            { "email": "bulk0@example.com", "password": "asdfasdfasdf", "kdfIterations": 5_000 },
+            { "email": "bulk2@example.com", "password": "asdfasdfasdf", "kdfIterations": 0 },
+        ]);
+        assert_eq!(
+            generate_user_keys_bulk_internal(&bad.to_string()).unwrap_err(),
+            SdkError::invalid_input("User 1: kdf_iterations must be non-zero")
+        );
+    }
+
+    #[test]
+    fn make_kdf_pbkdf2_ignores_argon2id_settings() {
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        assert_eq!(kdf_columns(&kdf), (KDF_PBKDF2, 5_000, None, None));
+    }
+
+    #[test]
+    fn make_kdf_rejects_invalid_settings() {
+        assert!(make_kdf(KDF_PBKDF2, 0, 0, 0).is_err());
+        assert!(make_kdf(KDF_ARGON2ID, 3, 0, 4).is_err());
+        assert!(make_kdf(KDF_ARGON2ID, 3, 64, 0).is_err());
+        assert!(make_kdf(7, 3, 64, 4).is_err());
+    }
+
+    #[test]
+    fn generate_key_connector_user_keys_wraps_user_key_with_random_master_key() {
+        let json = generate_key_connector_user_keys_internal(0).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let master_key = STANDARD
+            .decode(parsed["masterKey"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(master_key.len(), 32, "master key must be 32 bytes");
+
+        // Unlocking with the key connector's master key must recover the user key.
+        let stretched = stretch_master_key(&master_key).unwrap();
+        let user_key =
+            unwrap_key(parsed["encryptedUserKey"].as_str().unwrap(), &stretched).unwrap();
+        assert_eq!(
+            <String>::from(user_key.to_base64()),
+            parsed["key"].as_str().unwrap()
+        );
+        assert!(
+            parsed.get("masterPasswordHash").is_none(),
+            "key connector users have no master password"
+        );
+    }
 }
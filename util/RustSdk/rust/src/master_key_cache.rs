@@ -0,0 +1,165 @@
+//! Cache of derived master keys.
+//!
+//! Deriving a master key runs the account's full KDF, which at realistic settings (600,000 PBKDF2
+//! iterations, or Argon2id) dominates user generation. The Seeder derives the same
+//! (email, password, KDF) again for re-seeded rosters and for every step that needs a user's master
+//! key, so each derivation is cached with its server authorization hash.
+//!
+//! Entries are keyed by a SHA-256 hash of the inputs, so the cache holds no plaintext passwords. It is
+//! emptied when it reaches `MAX_ENTRIES`, and `clear_master_key_cache` empties it on demand.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, PoisonError, RwLock},
+};
+
+use bitwarden_crypto::{HashPurpose, Kdf, MasterKey};
+use sha2::{Digest, Sha256};
+
+use crate::crypto_util::{kdf_columns, SdkError};
+
+pub(crate) struct DerivedMasterKey {
+    pub(crate) master_key: MasterKey,
+    pub(crate) master_password_hash: String,
+}
+
+/// SHA-256 of (email, password, `kdf_columns`); see `cache_key`.
+type CacheKey = [u8; 32];
+
+/// Enough for the largest Seeder presets; past it the cache starts over rather than growing without bound.
+const MAX_ENTRIES: usize = 100_000;
+
+static CACHE: LazyLock<RwLock<HashMap<CacheKey, Arc<DerivedMasterKey>>>> =
+    LazyLock::new(Default::default);
+
+/// Derive the master key for `password` and `email` under `kdf`, or return the cached derivation.
+pub(crate) fn derive_master_key(
+    email: &str,
+    password: &str,
+    kdf: &Kdf,
+) -> Result<Arc<DerivedMasterKey>, SdkError> {
+    let cache_key = cache_key(email, password, kdf);
+    if let Some(derived) = CACHE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&cache_key)
+    {
+        return Ok(derived.clone());
+    }
+
+    // Derived outside the lock, so parallel callers do not serialize on the KDF.
+    let master_key = MasterKey::derive(password, email, kdf)
+        .map_err(|_| SdkError::crypto_failure("Failed to derive master key"))?;
+    let master_password_hash = master_key
+        .derive_master_key_hash(password.as_bytes(), HashPurpose::ServerAuthorization)
+        .to_string();
+    let derived = Arc::new(DerivedMasterKey {
+        master_key,
+        master_password_hash,
+    });
+
+    let mut cache = CACHE.write().unwrap_or_else(PoisonError::into_inner);
+    if cache.len() >= MAX_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(cache_key, derived.clone());
+    Ok(derived)
+}
+
+/// Hash the inputs of a derivation. Email and password are length-prefixed so no two input pairs
+/// share an encoding.
+fn cache_key(email: &str, password: &str, kdf: &Kdf) -> CacheKey {
+    let (kdf_type, iterations, memory, parallelism) = kdf_columns(kdf);
+    let mut hasher = Sha256::new();
+    for field in [email, password] {
+        hasher.update((field.len() as u64).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(kdf_type.to_le_bytes());
+    hasher.update(iterations.to_le_bytes());
+    for setting in [memory, parallelism] {
+        // 0 is not a valid Argon2id setting, so it stands in for "not set".
+        hasher.update(setting.unwrap_or(0).to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Drop every cached master key derivation.
+#[no_mangle]
+pub unsafe extern "C" fn clear_master_key_cache() {
+    CACHE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::{make_kdf, KDF_PBKDF2};
+    use std::sync::Mutex;
+
+    /// Serializes the tests here, since `clear_master_key_cache` empties the cache for all of them.
+    static CACHE_TESTS: Mutex<()> = Mutex::new(());
+
+    fn is_cached(email: &str, password: &str, kdf: &Kdf) -> bool {
+        CACHE
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(&cache_key(email, password, kdf))
+    }
+
+    #[test]
+    fn repeated_derivation_hits_the_cache() {
+        let _serial = CACHE_TESTS.lock().unwrap_or_else(PoisonError::into_inner);
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+
+        let first = derive_master_key("hit@example.com", "password", &kdf).unwrap();
+        let second = derive_master_key("hit@example.com", "password", &kdf).unwrap();
+        assert!(
+            Arc::ptr_eq(&first, &second),
+            "must return the cached derivation"
+        );
+    }
+
+    #[test]
+    fn changed_kdf_misses_the_cache() {
+        let _serial = CACHE_TESTS.lock().unwrap_or_else(PoisonError::into_inner);
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        let other_kdf = make_kdf(KDF_PBKDF2, 5_001, 0, 0).unwrap();
+
+        let first = derive_master_key("miss@example.com", "password", &kdf).unwrap();
+        let second = derive_master_key("miss@example.com", "password", &other_kdf).unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_ne!(first.master_password_hash, second.master_password_hash);
+        assert_ne!(
+            cache_key("miss@example.com", "password", &kdf),
+            cache_key("miss@example.com", "password", &other_kdf)
+        );
+    }
+
+    #[test]
+    fn clear_drops_cached_derivations() {
+        let _serial = CACHE_TESTS.lock().unwrap_or_else(PoisonError::into_inner);
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+
+        let first = derive_master_key("clear@example.com", "password", &kdf).unwrap();
+        assert!(is_cached("clear@example.com", "password", &kdf));
+
+        unsafe { clear_master_key_cache() };
+        assert!(!is_cached("clear@example.com", "password", &kdf));
+
+        let second = derive_master_key("clear@example.com", "password", &kdf).unwrap();
+        assert!(
+            !Arc::ptr_eq(&first, &second),
+            "must derive again after a clear"
+        );
+        assert_eq!(first.master_password_hash, second.master_password_hash);
+    }
+
+    #[test]
+    fn cache_key_separates_email_from_password() {
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        assert_ne!(cache_key("ab", "c", &kdf), cache_key("a", "bc", &kdf));
+    }
+}
@@ -1,13 +1,126 @@
-//! Provider key-wrapping functions for the Seeder.
+//! Provider key functions for the Seeder.
 //!
-//! Wraps one symmetric key under another so clients can unwrap it back into a `SymmetricCryptoKey` —
-//! e.g. a `ProviderOrganization.Key`, which is an organization's symmetric key wrapped with the
-//! provider's symmetric key. All crypto runs through the same `bitwarden_crypto` primitives real
-//! clients use; only ciphertext ever leaves this module.
+//! Generates a provider's symmetric key and RSA keypair, encapsulates the provider key to each
+//! `ProviderUser`'s public key (the `ProviderUser.Key` column), and wraps one symmetric key under
+//! another so clients can unwrap it back into a `SymmetricCryptoKey` — e.g. a `ProviderOrganization.Key`,
+//! which is an organization's symmetric key wrapped with the provider's symmetric key. All crypto runs
+//! through the same `bitwarden_crypto` primitives real clients use; only ciphertext ever leaves this
+//! module.
 
-use std::ffi::{c_char, CStr, CString};
+use std::ffi::{c_char, CStr};
 
-use crate::crypto_util::{error_response, parse_key, wrap_key};
+use rayon::prelude::*;
+
+use crate::crypto_util::{
+    encapsulate_key, error_response, ffi_boundary, parse_key, parse_public_key, success_response,
+    wrap_key, SdkError,
+};
+use crate::key_handle::resolve_key;
+use crate::{encapsulate_shared_key, pooled_organization_keys};
+
+/// Generate a provider's symmetric key, with an RSA keypair drawn from the pool and its private key
+/// wrapped by the new provider key.
+///
+/// # Arguments
+/// * `pool_index` - Index into the RSA keypair pool
+///
+/// # Returns
+/// JSON `{ "key": <base64>, "publicKey": <base64>, "privateKey": <EncString> }`, the shape of
+/// `generate_organization_keys`.
+#[no_mangle]
+pub unsafe extern "C" fn generate_provider_keys(pool_index: u32) -> *const c_char {
+    ffi_boundary(|| match pooled_organization_keys(pool_index) {
+        Ok(json) => success_response(&json),
+        Err(err) => error_response(err),
+    })
+}
+
+/// Encapsulate a provider key to a provider user's public key, producing the `ProviderUser.Key` of a
+/// confirmed membership. The provider counterpart of `generate_user_organization_key`.
+///
+/// # Arguments
+/// * `user_public_key` - The user's base64 SPKI public key
+/// * `provider_key_b64` - Base64-encoded provider symmetric key
+///
+/// # Returns
+/// EncString in format "4.{data}"
+///
+/// # Safety
+/// Both pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn generate_provider_user_key(
+    user_public_key: *const c_char,
+    provider_key_b64: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(user_public_key) = CStr::from_ptr(user_public_key).to_str() else {
+            return error_response(SdkError::invalid_utf8("user_public_key"));
+        };
+        let Ok(provider_key_b64) = CStr::from_ptr(provider_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("provider_key_b64"));
+        };
+
+        match encapsulate_shared_key(user_public_key, provider_key_b64) {
+            Ok(encapsulated) => success_response(&encapsulated),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Encapsulate a provider key to every provider user's public key in one call, in parallel across
+/// cores, for providers with many admins and service users.
+///
+/// # Arguments
+/// * `user_public_keys_json` - JSON array of base64 SPKI public keys
+/// * `provider_key_b64` - Base64-encoded provider symmetric key
+///
+/// # Returns
+/// JSON array of "4.{data}" EncStrings, in input order.
+///
+/// # Safety
+/// Both pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn generate_provider_user_keys(
+    user_public_keys_json: *const c_char,
+    provider_key_b64: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(user_public_keys_json) = CStr::from_ptr(user_public_keys_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("user_public_keys_json"));
+        };
+        let Ok(provider_key_b64) = CStr::from_ptr(provider_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("provider_key_b64"));
+        };
+
+        match generate_provider_user_keys_internal(user_public_keys_json, provider_key_b64) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn generate_provider_user_keys_internal(
+    user_public_keys_json: &str,
+    provider_key_b64: &str,
+) -> Result<String, SdkError> {
+    let public_keys: Vec<String> = serde_json::from_str(user_public_keys_json)
+        .map_err(|e| SdkError::invalid_input(format!("Invalid public keys JSON: {e}")))?;
+    let provider_key = parse_key(provider_key_b64)?;
+
+    // RSA-OAEP padding is never seeded, so the order keys are encapsulated in does not matter.
+    let encapsulated = public_keys
+        .par_iter()
+        .enumerate()
+        .map(|(index, public_key)| {
+            parse_public_key(public_key)
+                .and_then(|public_key| encapsulate_key(&provider_key, &public_key))
+                .map_err(|err| SdkError::new(err.kind, format!("Public key {index}: {err}")))
+        })
+        .collect::<Result<Vec<_>, SdkError>>()?;
+
+    serde_json::to_string(&encapsulated)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize result JSON"))
+}
 
 /// Wrap a symmetric key with another symmetric key, returning the wrapped key as an EncString.
 ///
@@ -30,53 +143,65 @@ pub unsafe extern "C" fn wrap_symmetric_key(
     key_to_wrap_b64: *const c_char,
     wrapping_key_b64: *const c_char,
 ) -> *const c_char {
-    let Ok(key_to_wrap_b64) = CStr::from_ptr(key_to_wrap_b64).to_str() else {
-        return error_response("Invalid UTF-8 in key_to_wrap_b64");
-    };
+    ffi_boundary(|| {
+        let Ok(key_to_wrap_b64) = CStr::from_ptr(key_to_wrap_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("key_to_wrap_b64"));
+        };
 
-    let Ok(wrapping_key_b64) = CStr::from_ptr(wrapping_key_b64).to_str() else {
-        return error_response("Invalid UTF-8 in wrapping_key_b64");
-    };
+        let Ok(wrapping_key_b64) = CStr::from_ptr(wrapping_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("wrapping_key_b64"));
+        };
 
-    let key_to_wrap = match parse_key(key_to_wrap_b64) {
-        Ok(key) => key,
-        Err(msg) => return error_response(&msg),
-    };
+        let key_to_wrap = match parse_key(key_to_wrap_b64) {
+            Ok(key) => key,
+            Err(err) => return error_response(err),
+        };
 
-    let wrapping_key = match parse_key(wrapping_key_b64) {
-        Ok(key) => key,
-        Err(msg) => return error_response(&msg),
-    };
+        let wrapping_key = match parse_key(wrapping_key_b64) {
+            Ok(key) => key,
+            Err(err) => return error_response(err),
+        };
 
-    match wrap_key(&key_to_wrap, &wrapping_key) {
-        Ok(wrapped) => CString::new(wrapped).unwrap().into_raw(),
-        Err(msg) => error_response(&msg),
-    }
+        match wrap_key(&key_to_wrap, &wrapping_key) {
+            Ok(wrapped) => success_response(&wrapped),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Wrap one registered key with another: `wrap_symmetric_key` by handle.
+///
+/// # Arguments
+/// * `key_to_wrap_handle` - Handle from `register_key` for the key to wrap
+/// * `wrapping_key_handle` - Handle from `register_key` for the key to wrap it with
+///
+/// # Returns
+/// EncString in format "2.{iv}|{data}|{mac}" whose decrypted plaintext is the encoded key bytes
+#[no_mangle]
+pub unsafe extern "C" fn wrap_symmetric_key_with_handles(
+    key_to_wrap_handle: u64,
+    wrapping_key_handle: u64,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let result = resolve_key(key_to_wrap_handle)
+            .and_then(|key_to_wrap| wrap_key(&key_to_wrap, &resolve_key(wrapping_key_handle)?));
+
+        match result {
+            Ok(wrapped) => success_response(&wrapped),
+            Err(err) => error_response(err),
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::crypto_util::unwrap_key;
-    use crate::free_c_string;
-    use bitwarden_crypto::{SymmetricCryptoKey, SymmetricKeyAlgorithm};
-
-    fn make_test_key() -> SymmetricCryptoKey {
-        SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac)
-    }
-
-    fn call_ffi_string(
-        func: unsafe extern "C" fn(*const c_char, *const c_char) -> *const c_char,
-        a: &str,
-        b: &str,
-    ) -> String {
-        let a_cstr = CString::new(a).unwrap();
-        let b_cstr = CString::new(b).unwrap();
-        let ptr = unsafe { func(a_cstr.as_ptr(), b_cstr.as_ptr()) };
-        let result = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_owned();
-        unsafe { free_c_string(ptr as *mut c_char) };
-        result
-    }
+    use crate::crypto_util::{
+        call_ffi_string, decapsulate_key, make_test_key, unwrap_key, ErrorKind,
+    };
+    use crate::pool_material;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use bitwarden_crypto::PrivateKey;
 
     #[test]
     fn wrap_symmetric_key_unwraps_back_to_original_key() {
@@ -86,7 +211,8 @@ mod tests {
         let organization_key_b64: String = organization_key.to_base64().into();
         let provider_key_b64: String = provider_key.to_base64().into();
 
-        let wrapped = call_ffi_string(wrap_symmetric_key, &organization_key_b64, &provider_key_b64);
+        let wrapped =
+            call_ffi_string(wrap_symmetric_key, &organization_key_b64, &provider_key_b64).unwrap();
         assert!(
             wrapped.starts_with("2."),
             "Expected a type-2 EncString, got: {wrapped}"
@@ -102,4 +228,31 @@ mod tests {
             "unwrapped key must equal the original organization key"
         );
     }
+
+    #[test]
+    fn provider_user_keys_decapsulate_to_the_provider_key() {
+        let provider: serde_json::Value =
+            serde_json::from_str(&pooled_organization_keys(21).unwrap()).unwrap();
+        let provider_key_b64 = provider["key"].as_str().unwrap();
+
+        let public_keys: Vec<String> = [4, 9]
+            .iter()
+            .map(|i| STANDARD.encode(pool_material(*i).public_der.as_ref()))
+            .collect();
+        let json = serde_json::to_string(&public_keys).unwrap();
+        let out = generate_provider_user_keys_internal(&json, provider_key_b64).unwrap();
+        let encapsulated: Vec<String> = serde_json::from_str(&out).unwrap();
+        assert_eq!(encapsulated.len(), 2);
+
+        for (encapsulated, i) in encapsulated.iter().zip([4, 9]) {
+            let private_key = PrivateKey::from_der(&pool_material(i).private_der).unwrap();
+            let provider_key = decapsulate_key(encapsulated, &private_key).unwrap();
+            assert_eq!(<String>::from(provider_key.to_base64()), provider_key_b64);
+        }
+
+        let err = generate_provider_user_keys_internal(r#"["not a key!"]"#, provider_key_b64)
+            .unwrap_err();
+        assert!(err.message.starts_with("Public key 0: "), "got: {err}");
+        assert_eq!(err.kind, ErrorKind::InvalidBase64);
+    }
 }
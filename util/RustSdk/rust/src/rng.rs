@@ -0,0 +1,465 @@
+//! Seedable randomness for reproducible Seeder fixtures.
+//!
+//! By default every key, IV and keypair comes from the OS RNG inside `bitwarden_crypto`. Once
+//! `set_rng_seed` is called, the helpers here draw them from a process-wide seeded ChaCha stream
+//! instead, so two Seeder runs of the same preset issuing the same calls in the same order produce
+//! byte-identical keys and ciphertext. `clear_rng_seed` switches back to the OS RNG.
+//!
+//! The stream is `ChaCha20Rng` rather than `StdRng`, whose algorithm rand may change in any release,
+//! so a seed keeps producing the same bytes across dependency bumps.
+//!
+//! Only AES-256-CBC-HMAC (type 2) encryption is made deterministic: IVs come from the seeded
+//! stream and the ciphertext is assembled here, since `bitwarden_crypto` draws its IVs internally and
+//! takes none from the caller. V2 accounts' XChaCha20-Poly1305 user keys and Ed25519 signing keys
+//! come from the stream too, but what they encrypt (type 7) keeps `bitwarden_crypto`'s random
+//! nonces. RSA keypairs are drawn from the pre-generated RSA pool rather than
+//! generated, while RSA-OAEP encapsulations keep `bitwarden_crypto`'s random padding, which it offers
+//! no way to seed.
+//!
+//! Each draw decides between the seeded stream and the OS RNG under the same lock it draws with, so
+//! a concurrent `set_rng_seed` or `clear_rng_seed` never splits one key or EncString across both.
+//! Unseeded draws check an atomic flag first and never take the lock.
+
+#[cfg(test)]
+use std::cell::{Cell, RefCell};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex, PoisonError,
+};
+
+use aes::Aes256;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bitwarden_crypto::{
+    BitwardenLegacyKeyBytes, CoseKeyBytes, CoseSerializable, CryptoError, EncString,
+    KeyEncryptable, MasterKey, SignatureAlgorithm, SigningKey, SymmetricCryptoKey,
+    SymmetricKeyAlgorithm,
+};
+use cbc::cipher::{block_padding::Pkcs7, BlockModeEncrypt, KeyIvInit};
+use hmac::{Hmac, KeyInit, Mac};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::Sha256;
+
+use crate::crypto_util::{stretch_master_key, SdkError};
+
+/// COSE key parameter labels (RFC 9052 section 7.1, RFC 9053 section 7.2).
+const COSE_KEY_ID: i64 = 2;
+const COSE_SYMMETRIC_KEY: i64 = -1;
+const COSE_OKP_PUBLIC_KEY: i64 = -2;
+const COSE_OKP_PRIVATE_KEY: i64 = -4;
+
+static SEEDED_RNG: Mutex<Option<ChaCha20Rng>> = Mutex::new(None);
+
+/// Whether `SEEDED_RNG` holds a stream. Only written while holding its lock.
+static SEEDED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(test)]
+thread_local! {
+    /// A stream seeded for the current test thread only; see `with_test_seed`.
+    static TEST_RNG: RefCell<Option<ChaCha20Rng>> = const { RefCell::new(None) };
+
+    /// Whether this thread called `set_rng_seed`. Tests run in parallel, so the process-wide stream
+    /// only serves the thread that seeded it and every other test keeps drawing from the OS RNG.
+    static SEEDED_HERE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Switch all key, IV and keypair generation to a deterministic stream seeded with `seed`.
+///
+/// Re-seeding restarts the stream, so a Seeder run that seeds once up front is reproducible.
+/// Determinism only holds for single-threaded callers that issue the same calls in the same order.
+///
+/// RSA-OAEP encapsulations stay random: the type-4 organization and provider keys encapsulated to
+/// members, e.g. by `generate_user_organization_key` and `generate_provider_user_key`, differ from
+/// run to run, so fixtures containing them are not byte-for-byte reproducible. The keys they
+/// encapsulate are. So are type-7 EncStrings, such as a V2 account's wrapped private and signing
+/// keys, while the V2 keys themselves are seeded.
+#[no_mangle]
+pub unsafe extern "C" fn set_rng_seed(seed: u64) {
+    let mut rng = SEEDED_RNG.lock().unwrap_or_else(PoisonError::into_inner);
+    *rng = Some(ChaCha20Rng::seed_from_u64(seed));
+    SEEDED.store(true, Ordering::Release);
+    #[cfg(test)]
+    SEEDED_HERE.set(true);
+}
+
+/// Switch key, IV and keypair generation back to the OS RNG.
+#[no_mangle]
+pub unsafe extern "C" fn clear_rng_seed() {
+    let mut rng = SEEDED_RNG.lock().unwrap_or_else(PoisonError::into_inner);
+    *rng = None;
+    SEEDED.store(false, Ordering::Release);
+    #[cfg(test)]
+    SEEDED_HERE.set(false);
+}
+
+/// Whether `set_rng_seed` is in effect.
+///
+/// Only a scheduling hint, e.g. to run bulk generation sequentially: a draw must not be split into an
+/// `is_seeded` check and a separate `fill_seeded`, since the seed may change in between.
+pub(crate) fn is_seeded() -> bool {
+    #[cfg(test)]
+    {
+        if TEST_RNG.with_borrow(Option::is_some) {
+            return true;
+        }
+        if !SEEDED_HERE.get() {
+            return false;
+        }
+    }
+    SEEDED.load(Ordering::Acquire)
+}
+
+/// Fill `bytes` from the seeded stream. Returns false, leaving `bytes` untouched, when unseeded.
+fn fill_seeded(bytes: &mut [u8]) -> bool {
+    #[cfg(test)]
+    {
+        let filled = TEST_RNG.with_borrow_mut(|rng| match rng.as_mut() {
+            Some(rng) => {
+                rng.fill_bytes(bytes);
+                true
+            }
+            None => false,
+        });
+        if filled {
+            return true;
+        }
+        if !SEEDED_HERE.get() {
+            return false;
+        }
+    }
+    if !SEEDED.load(Ordering::Acquire) {
+        return false;
+    }
+
+    let mut rng = SEEDED_RNG.lock().unwrap_or_else(PoisonError::into_inner);
+    match rng.as_mut() {
+        Some(rng) => {
+            rng.fill_bytes(bytes);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Random bytes from the seeded stream when seeded, otherwise from the OS-seeded thread RNG.
+pub(crate) fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    if !fill_seeded(&mut bytes) {
+        rand::rng().fill_bytes(&mut bytes);
+    }
+    bytes
+}
+
+/// Draw an index in `0..len` from the seeded stream, or `None` when unseeded.
+pub(crate) fn seeded_index(len: usize) -> Option<usize> {
+    let mut bytes = [0u8; 8];
+    fill_seeded(&mut bytes).then(|| (u64::from_le_bytes(bytes) % len as u64) as usize)
+}
+
+/// Make a new AES-256-CBC-HMAC key, from the seeded stream when seeded.
+pub(crate) fn make_symmetric_key() -> SymmetricCryptoKey {
+    let mut bytes = [0u8; 64];
+    if !fill_seeded(&mut bytes) {
+        return SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+    }
+    key_from_bytes(&bytes)
+}
+
+/// Make a new XChaCha20-Poly1305 key, from the seeded stream when seeded.
+pub(crate) fn make_xchacha20_poly1305_key() -> Result<SymmetricCryptoKey, SdkError> {
+    let key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::XChaCha20Poly1305);
+    let mut bytes = [0u8; 48];
+    if !fill_seeded(&mut bytes) {
+        return Ok(key);
+    }
+
+    let (key_id, key_bytes) = bytes.split_at(16);
+    let encoded = replace_cose_key_values(
+        key.to_encoded().as_ref(),
+        &[(COSE_KEY_ID, key_id), (COSE_SYMMETRIC_KEY, key_bytes)],
+    )?;
+    SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(encoded.as_slice()))
+        .map_err(|_| SdkError::crypto_failure("Failed to decode seeded XChaCha20-Poly1305 key"))
+}
+
+/// Make a new Ed25519 signing key, from the seeded stream when seeded.
+pub(crate) fn make_ed25519_signing_key() -> Result<SigningKey, SdkError> {
+    let key = SigningKey::make(SignatureAlgorithm::Ed25519);
+    let mut bytes = [0u8; 48];
+    if !fill_seeded(&mut bytes) {
+        return Ok(key);
+    }
+
+    let (key_id, secret) = bytes.split_at(16);
+    let secret: [u8; 32] = secret
+        .try_into()
+        .expect("48 - 16 bytes is an Ed25519 secret");
+    let public = ed25519_dalek::SigningKey::from_bytes(&secret)
+        .verifying_key()
+        .to_bytes();
+    let encoded = replace_cose_key_values(
+        key.to_cose().as_ref(),
+        &[
+            (COSE_KEY_ID, key_id),
+            (COSE_OKP_PRIVATE_KEY, &secret[..]),
+            (COSE_OKP_PUBLIC_KEY, &public[..]),
+        ],
+    )?;
+    SigningKey::from_cose(&CoseKeyBytes::from(encoded))
+        .map_err(|_| SdkError::crypto_failure("Failed to decode seeded signing key"))
+}
+
+/// Swap the byte-string parameters of a COSE key `bitwarden_crypto` generated for equally long seeded
+/// ones, so the key keeps exactly the encoding it would have had. Parameters the key lacks are
+/// skipped; anything after the CBOR item, such as padding, is kept as is.
+fn replace_cose_key_values(
+    encoded: &[u8],
+    replacements: &[(i64, &[u8])],
+) -> Result<Vec<u8>, SdkError> {
+    let unexpected = || SdkError::crypto_failure("Unexpected COSE key encoding");
+
+    let mut key: ciborium::Value = ciborium::from_reader(encoded).map_err(|_| unexpected())?;
+    for (label, value) in key.as_map_mut().ok_or_else(unexpected)? {
+        let Some(label) = label.as_integer().and_then(|l| i64::try_from(l).ok()) else {
+            continue;
+        };
+        let Some((_, replacement)) = replacements.iter().find(|(l, _)| *l == label) else {
+            continue;
+        };
+        match value.as_bytes_mut() {
+            Some(bytes) if bytes.len() == replacement.len() => bytes.copy_from_slice(replacement),
+            _ => return Err(unexpected()),
+        }
+    }
+
+    let mut replaced = Vec::with_capacity(encoded.len());
+    ciborium::into_writer(&key, &mut replaced).map_err(|_| unexpected())?;
+    // Every replacement keeps its length, so the key re-encodes to the same size.
+    replaced.extend_from_slice(encoded.get(replaced.len()..).ok_or_else(unexpected)?);
+    Ok(replaced)
+}
+
+pub(crate) fn key_from_bytes(bytes: &[u8; 64]) -> SymmetricCryptoKey {
+    SymmetricCryptoKey::try_from(&BitwardenLegacyKeyBytes::from(bytes.as_slice()))
+        .expect("64 bytes is a valid AES-256-CBC-HMAC key")
+}
+
+/// Encrypt `plaintext` with `key`, drawing the IV from the seeded stream when seeded.
+///
+/// Drop-in replacement for `plaintext.encrypt_with_key(key)`: unseeded, or for keys other than
+/// AES-256-CBC-HMAC, it delegates to `bitwarden_crypto` unchanged.
+pub(crate) fn encrypt<P>(plaintext: P, key: &SymmetricCryptoKey) -> Result<EncString, CryptoError>
+where
+    P: AsRef<[u8]> + KeyEncryptable<SymmetricCryptoKey, EncString>,
+{
+    if matches!(key, SymmetricCryptoKey::Aes256CbcHmacKey(_)) {
+        let mut iv = [0u8; 16];
+        if fill_seeded(&mut iv) {
+            return Ok(encrypt_aes256_cbc_hmac(plaintext.as_ref(), key, iv));
+        }
+    }
+    plaintext.encrypt_with_key(key)
+}
+
+/// Make a user key and wrap it with the stretched master key, from the seeded stream when seeded.
+pub(crate) fn make_user_key(
+    master_key: &MasterKey,
+) -> Result<(SymmetricCryptoKey, EncString), SdkError> {
+    // The key and the IV wrapping it are drawn together, so both come from the same source.
+    let mut bytes = [0u8; 80];
+    if !fill_seeded(&mut bytes) {
+        let (user_key, encrypted_user_key) = master_key
+            .make_user_key()
+            .map_err(|_| SdkError::crypto_failure("Failed to make user key"))?;
+        return Ok((user_key.0, encrypted_user_key));
+    }
+
+    let (key_bytes, iv) = bytes.split_at(64);
+    let user_key = key_from_bytes(key_bytes.try_into().expect("split at 64 bytes"));
+    let encrypted_user_key = wrap_user_key_with_iv(
+        master_key,
+        &user_key,
+        iv.try_into().expect("80 - 64 bytes is an IV"),
+    )?;
+    Ok((user_key, encrypted_user_key))
+}
+
+/// Wrap `user_key` with the stretched master key, drawing the IV from the seeded stream when seeded.
+pub(crate) fn wrap_user_key(
+    master_key: &MasterKey,
+    user_key: &SymmetricCryptoKey,
+) -> Result<EncString, SdkError> {
+    let mut iv = [0u8; 16];
+    if !fill_seeded(&mut iv) {
+        return master_key
+            .encrypt_user_key(user_key)
+            .map_err(|_| SdkError::crypto_failure("Failed to wrap user key"));
+    }
+    wrap_user_key_with_iv(master_key, user_key, iv)
+}
+
+fn wrap_user_key_with_iv(
+    master_key: &MasterKey,
+    user_key: &SymmetricCryptoKey,
+    iv: [u8; 16],
+) -> Result<EncString, SdkError> {
+    let master_key_bytes = STANDARD
+        .decode(<String>::from(master_key.to_base64()))
+        .map_err(|_| SdkError::crypto_failure("Failed to decode master key"))?;
+    let stretched = stretch_master_key(&master_key_bytes)?;
+    Ok(encrypt_aes256_cbc_hmac(
+        &user_key.to_encoded(),
+        &stretched,
+        iv,
+    ))
+}
+
+/// Run `f` with this thread's draws taken from a stream seeded with `seed`, leaving the process-wide
+/// seed to tests running in parallel.
+#[cfg(test)]
+pub(crate) fn with_test_seed<T>(seed: u64, f: impl FnOnce() -> T) -> T {
+    TEST_RNG.with_borrow_mut(|rng| *rng = Some(ChaCha20Rng::seed_from_u64(seed)));
+    let result = f();
+    TEST_RNG.with_borrow_mut(|rng| *rng = None);
+    result
+}
+
+/// AES-256-CBC with PKCS#7 padding, authenticated with HMAC-SHA256 over `iv | ciphertext`: the
+/// type-2 EncString every client decrypts. `key` must be an AES-256-CBC-HMAC key; `encrypt` and
+/// `wrap_user_key_with_iv` only pass those.
+fn encrypt_aes256_cbc_hmac(plaintext: &[u8], key: &SymmetricCryptoKey, iv: [u8; 16]) -> EncString {
+    let key_bytes = key.to_encoded();
+    let (enc_key, mac_key) = key_bytes.as_ref().split_at(32);
+
+    let data = cbc::Encryptor::<Aes256>::new_from_slices(enc_key, &iv)
+        .expect("AES-256 key and IV lengths are fixed")
+        .encrypt_padded_vec::<Pkcs7>(plaintext);
+
+    let mut hmac =
+        Hmac::<Sha256>::new_from_slice(mac_key).expect("HMAC accepts keys of any length");
+    hmac.update(&iv);
+    hmac.update(&data);
+    let mut mac = [0u8; 32];
+    mac.copy_from_slice(&hmac.finalize().into_bytes());
+
+    EncString::Aes256Cbc_HmacSha256_B64 { iv, mac, data }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+    use crate::cipher::encrypt_string;
+    use crate::crypto_util::{make_kdf, take_response, KDF_PBKDF2};
+    use crate::generate_user_keys;
+    use bitwarden_crypto::KeyDecryptable;
+
+    fn test_master_key() -> MasterKey {
+        let kdf = make_kdf(KDF_PBKDF2, 5_000, 0, 0).unwrap();
+        MasterKey::derive("password", "user@example.com", &kdf).unwrap()
+    }
+
+    #[test]
+    fn ffi_seed_reproduces_user_keys_and_ciphertext_until_cleared() {
+        let email = CString::new("seeded@example.com").unwrap();
+        let password = CString::new("asdfasdfasdf").unwrap();
+        let plaintext = CString::new("seeded plaintext").unwrap();
+        let generate = || {
+            let keys = take_response(unsafe {
+                generate_user_keys(email.as_ptr(), password.as_ptr(), 5_000, 3)
+            })
+            .unwrap();
+            let parsed: serde_json::Value = serde_json::from_str(&keys).unwrap();
+            let key = CString::new(parsed["key"].as_str().unwrap()).unwrap();
+            let encrypted =
+                take_response(unsafe { encrypt_string(plaintext.as_ptr(), key.as_ptr()) }).unwrap();
+            (keys, encrypted)
+        };
+
+        unsafe { set_rng_seed(42) };
+        let first = generate();
+        unsafe { set_rng_seed(42) };
+        let second = generate();
+        unsafe { clear_rng_seed() };
+        let unseeded = generate();
+
+        assert_eq!(
+            first, second,
+            "equal seeds must produce byte-identical output"
+        );
+        assert_ne!(unseeded.0, first.0);
+        assert_ne!(unseeded.1, first.1);
+    }
+
+    #[test]
+    fn encrypt_aes256_cbc_hmac_is_deterministic_and_decryptable() {
+        let key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let iv = [7u8; 16];
+
+        let first = encrypt_aes256_cbc_hmac(b"seeded plaintext", &key, iv);
+        let second = encrypt_aes256_cbc_hmac(b"seeded plaintext", &key, iv);
+        assert_eq!(first.to_string(), second.to_string());
+        assert!(first.to_string().starts_with("2."));
+
+        let decrypted: String = first.decrypt_with_key(&key).unwrap();
+        assert_eq!(decrypted, "seeded plaintext");
+    }
+
+    #[test]
+    fn encrypt_aes256_cbc_hmac_rejects_wrong_key() {
+        let key = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+        let other = SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac);
+
+        let encrypted = encrypt_aes256_cbc_hmac(b"secret", &key, [1u8; 16]);
+        let result: Result<String, _> = encrypted.decrypt_with_key(&other);
+        assert!(result.is_err(), "MAC must not verify under a different key");
+    }
+
+    #[test]
+    fn seeded_encryption_decrypts_with_bitwarden_crypto_for_every_key_type() {
+        let master_key = test_master_key();
+        let keys = [
+            SymmetricCryptoKey::make(SymmetricKeyAlgorithm::Aes256CbcHmac),
+            SymmetricCryptoKey::make(SymmetricKeyAlgorithm::XChaCha20Poly1305),
+            key_from_bytes(&[9u8; 64]),
+        ];
+
+        for key in &keys {
+            let encrypted =
+                with_test_seed(42, || encrypt("seeded plaintext".to_string(), key)).unwrap();
+            let decrypted: String = encrypted.decrypt_with_key(key).unwrap();
+            assert_eq!(decrypted, "seeded plaintext");
+        }
+
+        // Stretched master keys are the other AES-256-CBC-HMAC keys the hand-rolled encryption sees.
+        for user_key in [&keys[0], &keys[2]] {
+            let wrapped = with_test_seed(42, || wrap_user_key(&master_key, user_key)).unwrap();
+            let unwrapped = master_key.decrypt_user_key(wrapped).unwrap();
+            assert_eq!(
+                unwrapped.to_encoded().as_ref(),
+                user_key.to_encoded().as_ref()
+            );
+        }
+        let (user_key, wrapped) = with_test_seed(42, || make_user_key(&master_key)).unwrap();
+        let unwrapped = master_key.decrypt_user_key(wrapped).unwrap();
+        assert_eq!(
+            unwrapped.to_encoded().as_ref(),
+            user_key.to_encoded().as_ref()
+        );
+    }
+
+    #[test]
+    fn equal_test_seeds_produce_equal_user_keys() {
+        let master_key = test_master_key();
+
+        let (first_key, first) = with_test_seed(7, || make_user_key(&master_key)).unwrap();
+        let (second_key, second) = with_test_seed(7, || make_user_key(&master_key)).unwrap();
+        assert_eq!(first.to_string(), second.to_string());
+        assert_eq!(
+            first_key.to_encoded().as_ref(),
+            second_key.to_encoded().as_ref()
+        );
+        assert!(!is_seeded(), "the test seed must not outlive its closure");
+    }
+}
@@ -0,0 +1,360 @@
+//! Secrets Manager data encryption for the Seeder.
+//!
+//! Secrets Manager stores a `Project.Name` and a `Secret` key/value/note triple per row, each an
+//! EncString under the owning organization's key. `generate_secrets_manager_data` also builds the
+//! plaintext, in the shapes real secrets take: connection strings, API keys, PEM certificates and
+//! multi-line `.env` blocks. Randomness comes from `rng`, so seeded runs produce identical data.
+
+use std::ffi::{c_char, CStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+
+use bitwarden_crypto::SymmetricCryptoKey;
+
+use crate::attachment::encrypt_str;
+use crate::crypto_util::{error_response, ffi_boundary, parse_key, success_response, SdkError};
+use crate::rng;
+
+const PROJECT_NAMES: &[&str] = &[
+    "Production API",
+    "Staging API",
+    "Payments Service",
+    "Data Pipeline",
+    "Mobile Backend",
+    "Internal Tools",
+    "CI/CD",
+    "Analytics",
+];
+
+/// One in each run of this many generated secrets is left unassigned to any project.
+const UNASSIGNED_EVERY: usize = 5;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecretsManagerData {
+    projects: Vec<String>,
+    secrets: Vec<SecretData>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecretData {
+    key: String,
+    value: String,
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    project_indexes: Vec<usize>,
+}
+
+/// Encrypt Secrets Manager projects and secrets in bulk with an organization key.
+///
+/// # Arguments
+/// * `json` - `{ "projects": [<name>], "secrets": [{ "key", "value", "note", "projectIndexes": [<usize>] }] }`
+///   where `projectIndexes` index into `projects`
+/// * `organization_key_b64` - Base64-encoded organization symmetric key
+///
+/// # Returns
+/// JSON `{ "projects": [{ "name": <EncString> }], "secrets": [{ "key", "value", "note": <EncString>,
+/// "projectIndexes": [<usize>] }] }` in input order.
+///
+/// # Safety
+/// Both pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn encrypt_secrets_manager_data(
+    json: *const c_char,
+    organization_key_b64: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(json) = CStr::from_ptr(json).to_str() else {
+            return error_response(SdkError::invalid_utf8("json"));
+        };
+        let Ok(organization_key_b64) = CStr::from_ptr(organization_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("organization_key_b64"));
+        };
+
+        let result = serde_json::from_str::<SecretsManagerData>(json)
+            .map_err(|e| SdkError::invalid_input(format!("Invalid Secrets Manager JSON: {e}")))
+            .and_then(|data| {
+                encrypt_secrets_manager_data_internal(&data, &parse_key(organization_key_b64)?)
+            });
+
+        match result {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Generate `project_count` projects and `secret_count` realistic secrets and encrypt them with an
+/// organization key.
+///
+/// One secret in five is left unassigned, and the rest are spread round-robin across the projects.
+///
+/// # Arguments
+/// * `organization_key_b64` - Base64-encoded organization symmetric key
+/// * `project_count` - Number of projects to generate
+/// * `secret_count` - Number of secrets to generate
+///
+/// # Returns
+/// The `encrypt_secrets_manager_data` JSON.
+///
+/// # Safety
+/// `organization_key_b64` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn generate_secrets_manager_data(
+    organization_key_b64: *const c_char,
+    project_count: u32,
+    secret_count: u32,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(organization_key_b64) = CStr::from_ptr(organization_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("organization_key_b64"));
+        };
+
+        let data = generate_plaintext(project_count as usize, secret_count as usize);
+        let result = parse_key(organization_key_b64)
+            .and_then(|key| encrypt_secrets_manager_data_internal(&data, &key));
+
+        match result {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn encrypt_secrets_manager_data_internal(
+    data: &SecretsManagerData,
+    key: &SymmetricCryptoKey,
+) -> Result<String, SdkError> {
+    let projects = data
+        .projects
+        .iter()
+        .map(|name| Ok(serde_json::json!({ "name": encrypt_str(name, key)? })))
+        .collect::<Result<Vec<_>, SdkError>>()?;
+
+    let secrets = data
+        .secrets
+        .iter()
+        .map(|secret| {
+            if let Some(index) = secret
+                .project_indexes
+                .iter()
+                .find(|i| **i >= data.projects.len())
+            {
+                return Err(SdkError::invalid_input(format!(
+                    "Project index {index} is out of range"
+                )));
+            }
+            Ok(serde_json::json!({
+                "key": encrypt_str(&secret.key, key)?,
+                "value": encrypt_str(&secret.value, key)?,
+                "note": encrypt_str(&secret.note, key)?,
+                "projectIndexes": secret.project_indexes,
+            }))
+        })
+        .collect::<Result<Vec<_>, SdkError>>()?;
+
+    let result = serde_json::json!({ "projects": projects, "secrets": secrets });
+
+    serde_json::to_string(&result)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize Secrets Manager data"))
+}
+
+fn generate_plaintext(project_count: usize, secret_count: usize) -> SecretsManagerData {
+    let projects = (0..project_count)
+        .map(|i| match PROJECT_NAMES.get(i) {
+            Some(name) => name.to_string(),
+            None => format!(
+                "{} {}",
+                PROJECT_NAMES[i % PROJECT_NAMES.len()],
+                i / PROJECT_NAMES.len() + 1
+            ),
+        })
+        .collect();
+
+    // Counts assigned secrets only, so skipping the unassigned ones never skips a project.
+    let mut assigned = 0;
+    let secrets = (0..secret_count)
+        .map(|i| {
+            let project_indexes = if project_count == 0 || is_unassigned(i) {
+                Vec::new()
+            } else {
+                assigned += 1;
+                vec![(assigned - 1) % project_count]
+            };
+            let (key, value, note) = make_secret(i);
+            SecretData {
+                key,
+                value,
+                note,
+                project_indexes,
+            }
+        })
+        .collect();
+
+    SecretsManagerData { projects, secrets }
+}
+
+/// Whether the `i`-th secret is left out of every project: one in each run of `UNASSIGNED_EVERY`, at a
+/// position that moves from run to run, so no secret shape from `make_secret` is always the one left out.
+fn is_unassigned(i: usize) -> bool {
+    i % UNASSIGNED_EVERY == (i / UNASSIGNED_EVERY) % UNASSIGNED_EVERY
+}
+
+/// Build the `i`-th secret, cycling through the shapes real secrets take.
+fn make_secret(i: usize) -> (String, String, String) {
+    match i % 5 {
+        0 => (
+            format!("DATABASE_URL_{i}"),
+            format!(
+                "Server=tcp:sql-{}.database.example.com,1433;Database=app;User ID=svc_app;Password={};Encrypt=True;",
+                random_hex::<4>(),
+                random_b64::<18>()
+            ),
+            "Primary SQL Server connection string".to_string(),
+        ),
+        1 => (
+            format!("STRIPE_API_KEY_{i}"),
+            format!("sk_live_{}", random_hex::<24>()),
+            String::new(),
+        ),
+        2 => (
+            format!("TLS_CERTIFICATE_{i}"),
+            random_pem("CERTIFICATE"),
+            "Rotate before expiry".to_string(),
+        ),
+        3 => (
+            format!("APP_ENV_{i}"),
+            format!(
+                "REDIS_URL=redis://cache-{}:6379\nJWT_SECRET={}\nSMTP_PASSWORD={}\nFEATURE_FLAGS=beta,audit",
+                random_hex::<3>(),
+                random_b64::<32>(),
+                random_b64::<12>()
+            ),
+            "Multi-line .env block".to_string(),
+        ),
+        _ => (
+            format!("AWS_SECRET_ACCESS_KEY_{i}"),
+            random_b64::<30>(),
+            format!("Access key id AKIA{}", random_hex::<8>().to_uppercase()),
+        ),
+    }
+}
+
+fn random_hex<const N: usize>() -> String {
+    rng::random_bytes::<N>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn random_b64<const N: usize>() -> String {
+    STANDARD.encode(rng::random_bytes::<N>())
+}
+
+fn random_pem(label: &str) -> String {
+    let body = STANDARD.encode(rng::random_bytes::<720>());
+    let lines: Vec<&str> = body
+        .as_bytes()
+        .chunks(64)
+        .map(|line| std::str::from_utf8(line).expect("base64 is ASCII"))
+        .collect();
+    format!(
+        "-----BEGIN {label}-----\n{}\n-----END {label}-----",
+        lines.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::crypto_util::{decrypt_value, make_test_key};
+
+    #[test]
+    fn encrypt_secrets_manager_data_roundtrip() {
+        let key = make_test_key();
+        let data: SecretsManagerData = serde_json::from_str(
+            r#"{
+                "projects": ["Billing"],
+                "secrets": [{ "key": "API_KEY", "value": "line one\nline two", "projectIndexes": [0] }]
+            }"#,
+        )
+        .unwrap();
+
+        let json = encrypt_secrets_manager_data_internal(&data, &key).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            decrypt_value(&parsed["projects"][0]["name"], &key),
+            "Billing"
+        );
+        let secret = &parsed["secrets"][0];
+        assert_eq!(decrypt_value(&secret["key"], &key), "API_KEY");
+        assert_eq!(decrypt_value(&secret["value"], &key), "line one\nline two");
+        assert_eq!(decrypt_value(&secret["note"], &key), "");
+        assert_eq!(secret["projectIndexes"], serde_json::json!([0]));
+    }
+
+    #[test]
+    fn encrypt_secrets_manager_data_rejects_unknown_project() {
+        let data: SecretsManagerData = serde_json::from_str(
+            r#"{ "projects": [], "secrets": [{ "key": "K", "value": "V", "projectIndexes": [3] }] }"#,
+        )
+        .unwrap();
+
+        let err = encrypt_secrets_manager_data_internal(&data, &make_test_key()).unwrap_err();
+        assert!(err.message.contains("out of range"), "got: {err}");
+    }
+
+    #[test]
+    fn generate_plaintext_covers_every_secret_shape() {
+        let data = generate_plaintext(10, 25);
+
+        assert_eq!(data.projects.len(), 10);
+        assert_eq!(data.projects[9], "Staging API 2");
+        assert_eq!(data.secrets.len(), 25);
+        assert!(data.secrets[0].project_indexes.is_empty());
+        assert_eq!(data.secrets[3].project_indexes, vec![2]);
+        assert!(data.secrets[2]
+            .value
+            .starts_with("-----BEGIN CERTIFICATE-----\n"));
+        assert!(data.secrets[3].value.lines().count() > 1);
+    }
+
+    #[test]
+    fn generate_plaintext_assigns_every_project_and_every_shape() {
+        let data = generate_plaintext(10, 50);
+
+        let assigned: Vec<&SecretData> = data
+            .secrets
+            .iter()
+            .filter(|secret| !secret.project_indexes.is_empty())
+            .collect();
+        assert_eq!(assigned.len(), 40);
+
+        let mut per_project = [0; 10];
+        for secret in &assigned {
+            per_project[secret.project_indexes[0]] += 1;
+        }
+        assert_eq!(per_project, [4; 10]);
+
+        let shapes: HashSet<&str> = assigned
+            .iter()
+            .map(|secret| secret.key.rsplit_once('_').unwrap().0)
+            .collect();
+        assert_eq!(
+            shapes,
+            HashSet::from([
+                "DATABASE_URL",
+                "STRIPE_API_KEY",
+                "TLS_CERTIFICATE",
+                "APP_ENV",
+                "AWS_SECRET_ACCESS_KEY"
+            ])
+        );
+    }
+}
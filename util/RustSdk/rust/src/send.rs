@@ -0,0 +1,324 @@
+//! Bitwarden Send encryption for the Seeder.
+//!
+//! Every Send has its own random 16-byte Send key, stored as `Send.Key` encrypted with the owner's
+//! user key and shared in the Send link. The key that encrypts the Send's name, notes and payload is
+//! derived from it with HMAC-SHA256 keyed by "bitwarden-send" followed by an HKDF-SHA256 expand with
+//! info "send". A password-protected Send additionally stores a PBKDF2-SHA256 hash of the password
+//! salted with the Send key.
+
+use std::ffi::{c_char, CStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hkdf::Hkdf;
+use hmac::{Hmac, KeyInit, Mac};
+use pbkdf2::pbkdf2_array;
+use sha2::Sha256;
+
+use bitwarden_crypto::{OctetStreamBytes, SymmetricCryptoKey};
+
+use crate::attachment::{encrypt_buffer, encrypt_str};
+use crate::crypto_util::{
+    byte_slice, error_response, ffi_boundary, parse_key, success_response, SdkError,
+};
+use crate::rng;
+use crate::ByteBuffer;
+
+/// PBKDF2-SHA256 iterations clients hash a Send password with.
+const SEND_PASSWORD_ITERATIONS: u32 = 100_000;
+
+/// Encrypt a text Send for the owner of `user_key_b64` under a fresh Send key.
+///
+/// # Arguments
+/// * `user_key_b64` - Base64-encoded user key of the Send's owner
+/// * `name` - The plaintext Send name
+/// * `notes` - The plaintext private notes. Pass "" for none.
+/// * `text` - The plaintext text payload
+/// * `password` - The access password. Pass "" for none.
+///
+/// # Returns
+/// JSON `{ "sendKey": <base64>, "key": <EncString>, "name": <EncString>, "notes": <EncString|null>,
+/// "text": <EncString>, "password": <base64|null> }`. `key` and `password` map onto the `Send`
+/// columns of the same names; `sendKey` is the secret carried in the Send link.
+///
+/// # Safety
+/// All pointers must be valid null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn encrypt_text_send(
+    user_key_b64: *const c_char,
+    name: *const c_char,
+    notes: *const c_char,
+    text: *const c_char,
+    password: *const c_char,
+) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(user_key_b64) = CStr::from_ptr(user_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("user_key_b64"));
+        };
+        let Ok(name) = CStr::from_ptr(name).to_str() else {
+            return error_response(SdkError::invalid_utf8("name"));
+        };
+        let Ok(notes) = CStr::from_ptr(notes).to_str() else {
+            return error_response(SdkError::invalid_utf8("notes"));
+        };
+        let Ok(text) = CStr::from_ptr(text).to_str() else {
+            return error_response(SdkError::invalid_utf8("text"));
+        };
+        let Ok(password) = CStr::from_ptr(password).to_str() else {
+            return error_response(SdkError::invalid_utf8("password"));
+        };
+
+        match encrypt_text_send_internal(user_key_b64, name, notes, text, password) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+/// Encrypt a file Send for the owner of `user_key_b64` under a fresh Send key.
+///
+/// # Arguments
+/// * `user_key_b64` - Base64-encoded user key of the Send's owner
+/// * `name` - The plaintext Send name
+/// * `notes` - The plaintext private notes. Pass "" for none.
+/// * `file_bytes` / `file_len` - The plaintext file bytes
+/// * `file_name` - The plaintext file name
+/// * `password` - The access password. Pass "" for none.
+/// * `blob_out` - Receives the encrypted file on success and an empty buffer on failure; release it
+///   with `free_byte_buffer`
+///
+/// # Returns
+/// JSON `{ "sendKey", "key", "name", "notes", "password" }` as for `encrypt_text_send`, plus
+/// `"fileName": <EncString>` and `"size": <u64>`. The blob uses the same
+/// `0x02 | iv | mac | ciphertext` layout as `encrypt_attachment_buffer`.
+///
+/// # Safety
+/// `file_bytes` must point to `file_len` readable bytes (or be null when empty), the string pointers
+/// must be valid null-terminated strings, and `blob_out` must be valid for writes.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn encrypt_file_send(
+    user_key_b64: *const c_char,
+    name: *const c_char,
+    notes: *const c_char,
+    file_bytes: *const u8,
+    file_len: usize,
+    file_name: *const c_char,
+    password: *const c_char,
+    blob_out: *mut ByteBuffer,
+) -> *const c_char {
+    ffi_boundary(|| {
+        if blob_out.is_null() {
+            return error_response(SdkError::invalid_input("Invalid blob_out: null pointer"));
+        }
+        *blob_out = ByteBuffer::empty();
+
+        let Ok(user_key_b64) = CStr::from_ptr(user_key_b64).to_str() else {
+            return error_response(SdkError::invalid_utf8("user_key_b64"));
+        };
+        let Ok(name) = CStr::from_ptr(name).to_str() else {
+            return error_response(SdkError::invalid_utf8("name"));
+        };
+        let Ok(notes) = CStr::from_ptr(notes).to_str() else {
+            return error_response(SdkError::invalid_utf8("notes"));
+        };
+        let Ok(file_name) = CStr::from_ptr(file_name).to_str() else {
+            return error_response(SdkError::invalid_utf8("file_name"));
+        };
+        let Ok(password) = CStr::from_ptr(password).to_str() else {
+            return error_response(SdkError::invalid_utf8("password"));
+        };
+
+        let file_bytes = byte_slice(file_bytes, file_len);
+        match encrypt_file_send_internal(user_key_b64, name, notes, file_bytes, file_name, password)
+        {
+            Ok((json, blob)) => {
+                *blob_out = ByteBuffer::from_vec(blob);
+                success_response(&json)
+            }
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn encrypt_text_send_internal(
+    user_key_b64: &str,
+    name: &str,
+    notes: &str,
+    text: &str,
+    password: &str,
+) -> Result<String, SdkError> {
+    let (mut result, encryption_key) = encrypt_send_common(user_key_b64, name, notes, password)?;
+    result["text"] = encrypt_str(text, &encryption_key)?.into();
+
+    serde_json::to_string(&result).map_err(|_| SdkError::crypto_failure("Failed to serialize send"))
+}
+
+fn encrypt_file_send_internal(
+    user_key_b64: &str,
+    name: &str,
+    notes: &str,
+    file_bytes: &[u8],
+    file_name: &str,
+    password: &str,
+) -> Result<(String, Vec<u8>), SdkError> {
+    let (mut result, encryption_key) = encrypt_send_common(user_key_b64, name, notes, password)?;
+    let blob = encrypt_buffer(file_bytes, &encryption_key)?;
+    result["fileName"] = encrypt_str(file_name, &encryption_key)?.into();
+    result["size"] = (blob.len() as u64).into();
+
+    let json = serde_json::to_string(&result)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize send"))?;
+    Ok((json, blob))
+}
+
+/// Make a Send key, wrap it with the user key, and encrypt the fields every Send type shares.
+/// Returns the partial JSON result and the derived key that encrypts the type-specific payload.
+fn encrypt_send_common(
+    user_key_b64: &str,
+    name: &str,
+    notes: &str,
+    password: &str,
+) -> Result<(serde_json::Value, SymmetricCryptoKey), SdkError> {
+    let user_key = parse_key(user_key_b64)?;
+    let send_key: [u8; 16] = rng::random_bytes();
+    let encryption_key = derive_send_key(&send_key)?;
+
+    let wrapped_send_key = rng::encrypt(&OctetStreamBytes::from(send_key.to_vec()), &user_key)
+        .map_err(|_| SdkError::crypto_failure("Failed to wrap send key"))?;
+    let notes = match notes {
+        "" => None,
+        notes => Some(encrypt_str(notes, &encryption_key)?),
+    };
+    let password = match password {
+        "" => None,
+        password => Some(STANDARD.encode(hash_send_password(
+            password,
+            &send_key,
+            SEND_PASSWORD_ITERATIONS,
+        ))),
+    };
+
+    let result = serde_json::json!({
+        "sendKey": STANDARD.encode(send_key),
+        "key": wrapped_send_key.to_string(),
+        "name": encrypt_str(name, &encryption_key)?,
+        "notes": notes,
+        "password": password,
+    });
+
+    Ok((result, encryption_key))
+}
+
+/// Derive the AES-256-CBC-HMAC key that encrypts a Send's contents from its 16-byte Send key, as
+/// clients do: HMAC-SHA256 keyed by "bitwarden-send" as the PRK, then an HKDF-SHA256 expand to 64
+/// bytes with info "send".
+fn derive_send_key(send_key: &[u8; 16]) -> Result<SymmetricCryptoKey, SdkError> {
+    let mut hmac = <Hmac<Sha256> as KeyInit>::new_from_slice(b"bitwarden-send")
+        .expect("HMAC accepts keys of any length");
+    hmac.update(send_key);
+    let prk = hmac.finalize().into_bytes();
+
+    let hkdf = Hkdf::<Sha256>::from_prk(&prk)
+        .map_err(|_| SdkError::crypto_failure("Failed to derive send key: invalid PRK length"))?;
+    let mut derived = [0u8; 64];
+    hkdf.expand(b"send", &mut derived)
+        .map_err(|_| SdkError::crypto_failure("Failed to derive send key"))?;
+    Ok(rng::key_from_bytes(&derived))
+}
+
+/// PBKDF2-HMAC-SHA256 of `password`, salted with the Send key, 32 bytes long.
+fn hash_send_password(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    pbkdf2_array::<Hmac<Sha256>, 32>(password.as_bytes(), salt, iterations)
+        .expect("HMAC accepts keys of any length")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto_util::{decrypt_value, make_test_key};
+    use bitwarden_crypto::{EncString, KeyDecryptable};
+
+    #[test]
+    fn text_send_decrypts_with_key_derived_from_wrapped_send_key() {
+        let user_key = make_test_key();
+        let user_key_b64: String = user_key.to_base64().into();
+
+        let json = encrypt_text_send_internal(&user_key_b64, "Wi-Fi", "", "hunter2", "").unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        // A recipient unwraps Send.Key with the user key and derives the content key from it.
+        let key_enc: EncString = parsed["key"].as_str().unwrap().parse().unwrap();
+        let send_key: Vec<u8> = key_enc.decrypt_with_key(&user_key).unwrap();
+        assert_eq!(
+            STANDARD.encode(&send_key),
+            parsed["sendKey"].as_str().unwrap()
+        );
+        let encryption_key = derive_send_key(&send_key.try_into().unwrap()).unwrap();
+
+        assert_eq!(decrypt_value(&parsed["name"], &encryption_key), "Wi-Fi");
+        assert_eq!(decrypt_value(&parsed["text"], &encryption_key), "hunter2");
+        assert!(parsed["notes"].is_null());
+        assert!(parsed["password"].is_null());
+    }
+
+    #[test]
+    fn file_send_blob_decrypts_with_derived_key() {
+        let user_key = make_test_key();
+        let user_key_b64: String = user_key.to_base64().into();
+        let data = b"file send contents";
+
+        let (json, blob) = encrypt_file_send_internal(
+            &user_key_b64,
+            "Report",
+            "quarterly",
+            data,
+            "report.pdf",
+            "s3cret",
+        )
+        .unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let send_key: [u8; 16] = STANDARD
+            .decode(parsed["sendKey"].as_str().unwrap())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let encryption_key = derive_send_key(&send_key).unwrap();
+
+        assert_eq!(
+            decrypt_value(&parsed["fileName"], &encryption_key),
+            "report.pdf"
+        );
+        assert_eq!(
+            decrypt_value(&parsed["notes"], &encryption_key),
+            "quarterly"
+        );
+        assert_eq!(parsed["size"].as_u64().unwrap(), blob.len() as u64);
+        let decrypted: Vec<u8> = EncString::from_buffer(&blob)
+            .unwrap()
+            .decrypt_with_key(&encryption_key)
+            .unwrap();
+        assert_eq!(decrypted, data);
+
+        let expected = hash_send_password("s3cret", &send_key, SEND_PASSWORD_ITERATIONS);
+        assert_eq!(
+            parsed["password"].as_str().unwrap(),
+            STANDARD.encode(expected)
+        );
+    }
+
+    #[test]
+    fn hash_send_password_matches_known_answer() {
+        // Computed independently as clients do: PBKDF2-HMAC-SHA256 of the password, salted with the
+        // raw Send key, 100,000 iterations, 32 bytes.
+        let send_key: [u8; 16] = std::array::from_fn(|i| i as u8);
+        assert_eq!(
+            STANDARD.encode(hash_send_password(
+                "correct horse battery staple",
+                &send_key,
+                SEND_PASSWORD_ITERATIONS
+            )),
+            "SdScJfWXhGIJ8Nkud3CrZOHHXpS0zmxQkmXuZxddKh4="
+        );
+    }
+}
@@ -0,0 +1,391 @@
+//! Integrity verifier for seeded vaults ("vault fsck").
+//!
+//! Takes one user's key material plus everything the Seeder wrote for them — organization
+//! memberships, ciphers, folders, collections and attachment metadata — and tries to decrypt all of
+//! it the way a client would. Every item that fails is reported with the reason, rather than stopping
+//! at the first, so one run after a large preset surfaces every wrong key and unencrypted path.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use bitwarden_crypto::{EncString, KeyDecryptable, SymmetricCryptoKey};
+
+use crate::attachment::{decrypt_buffer, decrypt_str, detect_attachment_keys};
+use crate::cipher::map_segments;
+use crate::crypto_util::{
+    decapsulate_key, error_response, ffi_boundary, parse_key, success_response, unwrap_key,
+    unwrap_private_key, ErrorKind, SdkError,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultInput {
+    /// Base64-encoded user key.
+    user_key: String,
+    /// The user's `PrivateKey` EncString, wrapped by the user key. Required to check organizations.
+    #[serde(default)]
+    private_key: Option<String>,
+    #[serde(default)]
+    organizations: Vec<OrganizationInput>,
+    /// Dot-notation field paths (with `[*]`) that must be encrypted on every cipher.
+    #[serde(default)]
+    cipher_field_paths: Vec<String>,
+    #[serde(default)]
+    ciphers: Vec<CipherInput>,
+    #[serde(default)]
+    folders: Vec<NamedInput>,
+    #[serde(default)]
+    collections: Vec<NamedInput>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OrganizationInput {
+    id: String,
+    /// `OrganizationUser.Key`: the organization key encapsulated to the user's public key.
+    key: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CipherInput {
+    id: String,
+    #[serde(default)]
+    organization_id: Option<String>,
+    /// The cipher's wrapped `Key`, if it is a cipher-key cipher.
+    #[serde(default)]
+    key: Option<String>,
+    /// The encrypted cipher JSON, checked at `cipherFieldPaths`.
+    data: serde_json::Value,
+    #[serde(default)]
+    attachments: Vec<AttachmentInput>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentInput {
+    id: String,
+    #[serde(default)]
+    key: Option<String>,
+    file_name: String,
+    /// Base64 EncArrayBuffer blob. When absent only the key and filename are checked.
+    #[serde(default)]
+    blob: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NamedInput {
+    id: String,
+    /// Set for collections; folders belong to the user.
+    #[serde(default)]
+    organization_id: Option<String>,
+    name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Failure {
+    kind: &'static str,
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    reason: String,
+}
+
+#[derive(Default)]
+struct Report {
+    checked: u64,
+    failures: Vec<Failure>,
+}
+
+impl Report {
+    /// Record a failure for `result` if it is an error, returning its value otherwise.
+    fn check<T>(&mut self, kind: &'static str, id: &str, result: Result<T, SdkError>) -> Option<T> {
+        result
+            .map_err(|err| {
+                self.failures.push(Failure {
+                    kind,
+                    id: id.to_string(),
+                    path: None,
+                    reason: err.message,
+                })
+            })
+            .ok()
+    }
+}
+
+/// Try to decrypt everything seeded for one user and report what fails.
+///
+/// # Arguments
+/// * `vault_json` - JSON
+///   `{ "userKey", "privateKey", "organizations": [{ "id", "key" }], "cipherFieldPaths": [<path>],
+///   "ciphers": [{ "id", "organizationId", "key", "data": {...}, "attachments": [{ "id", "key",
+///   "fileName", "blob" }] }], "folders": [{ "id", "name" }], "collections": [{ "id",
+///   "organizationId", "name" }] }`
+///
+/// # Returns
+/// JSON `{ "checked": <u64>, "failures": [{ "kind", "id", "path"?, "reason" }] }`. `kind` is one of
+/// `privateKey`, `organization`, `cipher`, `attachment`, `folder` or `collection`; an empty
+/// `failures` means every item decrypted.
+///
+/// # Safety
+/// `vault_json` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn verify_vault(vault_json: *const c_char) -> *const c_char {
+    ffi_boundary(|| {
+        let Ok(vault_json) = CStr::from_ptr(vault_json).to_str() else {
+            return error_response(SdkError::invalid_utf8("vault_json"));
+        };
+
+        match verify_vault_internal(vault_json) {
+            Ok(json) => success_response(&json),
+            Err(err) => error_response(err),
+        }
+    })
+}
+
+fn verify_vault_internal(vault_json: &str) -> Result<String, SdkError> {
+    let input: VaultInput = serde_json::from_str(vault_json)
+        .map_err(|e| SdkError::invalid_input(format!("Invalid vault JSON: {e}")))?;
+    let user_key = parse_key(&input.user_key)?;
+    let mut report = Report::default();
+
+    let private_key = input.private_key.as_deref().and_then(|wrapped| {
+        report.checked += 1;
+        report.check("privateKey", "", unwrap_private_key(wrapped, &user_key))
+    });
+
+    let mut organization_keys = HashMap::new();
+    for organization in &input.organizations {
+        report.checked += 1;
+        let result = match &private_key {
+            Some(private_key) => decapsulate_key(&organization.key, private_key),
+            None => Err(SdkError::invalid_input(
+                "No usable private key to decapsulate the organization key",
+            )),
+        };
+        if let Some(key) = report.check("organization", &organization.id, result) {
+            organization_keys.insert(organization.id.as_str(), key);
+        }
+    }
+
+    // The key an item owned by `organization_id` (or the user, when None) is encrypted with.
+    let owner_key = |organization_id: &Option<String>| -> Result<SymmetricCryptoKey, SdkError> {
+        match organization_id {
+            None => Ok(user_key.clone()),
+            Some(id) => organization_keys
+                .get(id.as_str())
+                .cloned()
+                .ok_or_else(|| SdkError::invalid_input(format!("No key for organization {id}"))),
+        }
+    };
+
+    for cipher in &input.ciphers {
+        report.checked += 1;
+        let Some(vault_key) =
+            report.check("cipher", &cipher.id, owner_key(&cipher.organization_id))
+        else {
+            continue;
+        };
+        let cipher_key = match cipher.key.as_deref() {
+            Some(wrapped) => {
+                match report.check("cipher", &cipher.id, unwrap_key(wrapped, &vault_key)) {
+                    Some(cipher_key) => Some(cipher_key),
+                    None => continue,
+                }
+            }
+            None => None,
+        };
+
+        let fields_key = cipher_key.as_ref().unwrap_or(&vault_key);
+        check_cipher_fields(&mut report, cipher, &input.cipher_field_paths, fields_key);
+
+        for attachment in &cipher.attachments {
+            report.checked += 1;
+            let result = check_attachment(attachment, vault_key.clone(), cipher_key.clone());
+            report.check("attachment", &attachment.id, result);
+        }
+    }
+
+    for folder in &input.folders {
+        report.checked += 1;
+        report.check("folder", &folder.id, decrypt_str(&folder.name, &user_key));
+    }
+
+    for collection in &input.collections {
+        report.checked += 1;
+        let result = owner_key(&collection.organization_id)
+            .and_then(|key| decrypt_str(&collection.name, &key));
+        report.check("collection", &collection.id, result);
+    }
+
+    let result = serde_json::json!({
+        "checked": report.checked,
+        "failures": report.failures,
+    });
+
+    serde_json::to_string(&result)
+        .map_err(|_| SdkError::crypto_failure("Failed to serialize vault report"))
+}
+
+/// Check every string at `paths` on the cipher decrypts, recording one failure per bad value.
+fn check_cipher_fields(
+    report: &mut Report,
+    cipher: &CipherInput,
+    paths: &[String],
+    key: &SymmetricCryptoKey,
+) {
+    let failures = RefCell::new(Vec::new());
+    let mut data = cipher.data.clone();
+
+    for path in paths {
+        let segments: Vec<&str> = path.split('.').collect();
+        // Values are left unchanged; the walk only records what fails to decrypt.
+        let _ = map_segments(&mut data, &segments, &|_, s| {
+            let decrypted = s
+                .parse::<EncString>()
+                .map_err(|_| {
+                    SdkError::invalid_input("Value is not an EncString; was the path encrypted?")
+                })
+                .and_then(|enc| {
+                    let result: Result<String, _> = enc.decrypt_with_key(key);
+                    result.map_err(SdkError::from_crypto(
+                        ErrorKind::DecryptionFailed,
+                        "Failed to decrypt",
+                    ))
+                });
+            if let Err(err) = decrypted {
+                failures.borrow_mut().push(Failure {
+                    kind: "cipher",
+                    id: cipher.id.clone(),
+                    path: Some(path.clone()),
+                    reason: err.message,
+                });
+            }
+            Ok(s.to_string())
+        });
+    }
+
+    report.failures.extend(failures.into_inner());
+}
+
+fn check_attachment(
+    attachment: &AttachmentInput,
+    vault_key: SymmetricCryptoKey,
+    cipher_key: Option<SymmetricCryptoKey>,
+) -> Result<(), SdkError> {
+    let (_, data_key, filename_key) = detect_attachment_keys(
+        attachment.key.as_deref().unwrap_or(""),
+        vault_key,
+        cipher_key,
+    )?;
+    decrypt_str(&attachment.file_name, &filename_key)?;
+    if let Some(blob_b64) = &attachment.blob {
+        let blob = STANDARD
+            .decode(blob_b64)
+            .map_err(|_| SdkError::invalid_base64("Failed to decode base64 blob"))?;
+        decrypt_buffer(&blob, &data_key)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attachment::encrypt_str;
+    use crate::crypto_util::{encapsulate_key, make_test_key, parse_public_key, wrap_key};
+    use crate::keypair;
+
+    fn verify(input: serde_json::Value) -> serde_json::Value {
+        serde_json::from_str(&verify_vault_internal(&input.to_string()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn verify_vault_accepts_well_formed_vault() {
+        let user_key = make_test_key();
+        let user_keypair = keypair(&user_key, 17).unwrap();
+        let organization_key = make_test_key();
+        let public_key = parse_public_key(&user_keypair.public.to_string()).unwrap();
+        let cipher_key = make_test_key();
+
+        let report = verify(serde_json::json!({
+            "userKey": user_key.to_base64(),
+            "privateKey": user_keypair.private.to_string(),
+            "organizations": [
+                { "id": "org", "key": encapsulate_key(&organization_key, &public_key).unwrap() }
+            ],
+            "cipherFieldPaths": ["name", "login.uris[*].uri"],
+            "ciphers": [
+                {
+                    "id": "personal",
+                    "data": { "name": encrypt_str("Mine", &user_key).unwrap() }
+                },
+                {
+                    "id": "shared",
+                    "organizationId": "org",
+                    "key": wrap_key(&cipher_key, &organization_key).unwrap(),
+                    "data": {
+                        "name": encrypt_str("Ours", &cipher_key).unwrap(),
+                        "login": { "uris": [{ "uri": encrypt_str("https://a", &cipher_key).unwrap() }] }
+                    },
+                    "attachments": [{
+                        "id": "att",
+                        "key": wrap_key(&make_test_key(), &cipher_key).unwrap(),
+                        "fileName": encrypt_str("a.txt", &cipher_key).unwrap()
+                    }]
+                }
+            ],
+            "folders": [{ "id": "folder", "name": encrypt_str("Work", &user_key).unwrap() }],
+            "collections": [
+                { "id": "col", "organizationId": "org", "name": encrypt_str("Eng", &organization_key).unwrap() }
+            ]
+        }));
+
+        assert_eq!(report["failures"], serde_json::json!([]));
+        // privateKey, organization, two ciphers, attachment, folder, collection
+        assert_eq!(report["checked"].as_u64().unwrap(), 7);
+    }
+
+    #[test]
+    fn verify_vault_reports_every_failure() {
+        let user_key = make_test_key();
+        let other_key = make_test_key();
+
+        let report = verify(serde_json::json!({
+            "userKey": user_key.to_base64(),
+            "cipherFieldPaths": ["name", "notes"],
+            "ciphers": [
+                {
+                    "id": "unencrypted",
+                    "data": { "name": "plaintext", "notes": encrypt_str("ok", &user_key).unwrap() }
+                },
+                {
+                    "id": "orphan",
+                    "organizationId": "missing",
+                    "data": {}
+                }
+            ],
+            "folders": [{ "id": "wrong-key", "name": encrypt_str("Work", &other_key).unwrap() }]
+        }));
+
+        let failures = report["failures"].as_array().unwrap();
+        let summary: Vec<(&str, &str)> = failures
+            .iter()
+            .map(|f| (f["kind"].as_str().unwrap(), f["id"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("cipher", "unencrypted"),
+                ("cipher", "orphan"),
+                ("folder", "wrong-key")
+            ]
+        );
+        assert_eq!(failures[0]["path"].as_str().unwrap(), "name");
+    }
+}
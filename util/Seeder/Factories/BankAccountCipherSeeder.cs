@@ -8,18 +8,18 @@ internal static class BankAccountCipherSeeder
 {
     internal static Cipher Create(CipherSeed options)
     {
-        var cipherView = new CipherViewDto
-        {
-            OrganizationId = options.OrganizationId,
-            Name = options.Name,
-            Notes = options.Notes,
-            Type = CipherTypes.BankAccount,
-            BankAccount = options.BankAccount,
-            Fields = options.Fields,
-            Reprompt = (int)options.Reprompt
-        };
-
-        var encrypted = CipherEncryption.Encrypt(cipherView, options.EncryptionKey!, options.CipherEncryption);
+        var encrypted = CipherEncryption.Encrypt(BuildView(options), options.EncryptionKey!, options.CipherEncryption);
         return CipherEncryption.CreateEntity(encrypted, encrypted.ToBankAccountData(), CipherType.BankAccount, options.OrganizationId, options.UserId);
     }
+
+    internal static CipherViewDto BuildView(CipherSeed options) => new()
+    {
+        OrganizationId = options.OrganizationId,
+        Name = options.Name,
+        Notes = options.Notes,
+        Type = CipherTypes.BankAccount,
+        BankAccount = options.BankAccount,
+        Fields = options.Fields,
+        Reprompt = (int)options.Reprompt
+    };
 }
@@ -8,20 +8,18 @@ internal static class CardCipherSeeder
 {
     internal static Cipher Create(CipherSeed options)
     {
-
-        var cipherView = new CipherViewDto
-        {
-            OrganizationId = options.OrganizationId,
-            Name = options.Name,
-            Notes = options.Notes,
-            Type = CipherTypes.Card,
-            Card = options.Card,
-            Fields = options.Fields,
-            Reprompt = (int)options.Reprompt
-        };
-
-        var encrypted = CipherEncryption.Encrypt(cipherView, options.EncryptionKey!, options.CipherEncryption);
+        var encrypted = CipherEncryption.Encrypt(BuildView(options), options.EncryptionKey!, options.CipherEncryption);
         return CipherEncryption.CreateEntity(encrypted, encrypted.ToCardData(), CipherType.Card, options.OrganizationId, options.UserId);
     }
 
+    internal static CipherViewDto BuildView(CipherSeed options) => new()
+    {
+        OrganizationId = options.OrganizationId,
+        Name = options.Name,
+        Notes = options.Notes,
+        Type = CipherTypes.Card,
+        Card = options.Card,
+        Fields = options.Fields,
+        Reprompt = (int)options.Reprompt
+    };
 }
@@ -11,12 +11,13 @@ using Bit.Seeder.Models;
 namespace Bit.Seeder.Factories;
 
 /// <summary>
-/// Composes cipher entities from generated data, handling encryption and ownership assignment.
-/// Used by generation steps to create realistic ciphers for organizations or personal vaults.
+/// Composes cipher seeds from generated data, handling ownership assignment.
+/// Used by generation steps to create realistic ciphers for organizations or personal vaults; the steps encrypt
+/// the seeds in bulk with <see cref="CipherEncryption.CreateMany"/>.
 /// </summary>
 internal static class CipherComposer
 {
-    internal static Cipher Compose(
+    internal static CipherSeed Compose(
         int index,
         CipherType cipherType,
         string encryptionKey,
@@ -38,7 +39,7 @@ internal static class CipherComposer
         };
     }
 
-    private static Cipher ComposeLogin(
+    private static CipherSeed ComposeLogin(
         int index,
         string encryptionKey,
         Company[] companies,
@@ -61,7 +62,7 @@ internal static class CipherComposer
             ? BuildPasswordHistory(index, 1 + (index % 3), generator.CipherCount, passwordDistribution)
             : null;
 
-        return LoginCipherSeeder.Create(new CipherSeed
+        return new CipherSeed
         {
             Type = CipherType.Login,
             Name = $"{company.Name} ({company.Category})",
@@ -77,7 +78,7 @@ internal static class CipherComposer
                 Fido2Credentials = fido2Credentials,
                 PasswordHistory = passwordHistory
             }
-        });
+        };
     }
 
     internal static List<PasswordHistoryViewDto> BuildPasswordHistory(
@@ -116,7 +117,7 @@ internal static class CipherComposer
         return history;
     }
 
-    private static Cipher ComposeCard(
+    private static CipherSeed ComposeCard(
         int index,
         string encryptionKey,
         GeneratorContext generator,
@@ -125,7 +126,7 @@ internal static class CipherComposer
         CipherRepromptType reprompt = CipherRepromptType.None)
     {
         var card = generator.Card.GenerateByIndex(index);
-        return CardCipherSeeder.Create(new CipherSeed
+        return new CipherSeed
         {
             Type = CipherType.Card,
             Name = $"{card.CardholderName}'s {card.Brand}",
@@ -134,10 +135,10 @@ internal static class CipherComposer
             UserId = userId,
             Reprompt = reprompt,
             Card = card
-        });
+        };
     }
 
-    private static Cipher ComposeIdentity(
+    private static CipherSeed ComposeIdentity(
         int index,
         string encryptionKey,
         GeneratorContext generator,
@@ -151,7 +152,7 @@ internal static class CipherComposer
         {
             name += $" ({identity.Company})";
         }
-        return IdentityCipherSeeder.Create(new CipherSeed
+        return new CipherSeed
         {
             Type = CipherType.Identity,
             Name = name,
@@ -160,10 +161,10 @@ internal static class CipherComposer
             UserId = userId,
             Reprompt = reprompt,
             Identity = identity
-        });
+        };
     }
 
-    private static Cipher ComposeSecureNote(
+    private static CipherSeed ComposeSecureNote(
         int index,
         string encryptionKey,
         GeneratorContext generator,
@@ -172,7 +173,7 @@ internal static class CipherComposer
         CipherRepromptType reprompt = CipherRepromptType.None)
     {
         var (name, notes) = generator.SecureNote.GenerateByIndex(index);
-        return SecureNoteCipherSeeder.Create(new CipherSeed
+        return new CipherSeed
         {
             Type = CipherType.SecureNote,
             Name = name,
@@ -181,10 +182,10 @@ internal static class CipherComposer
             OrganizationId = organizationId,
             UserId = userId,
             Reprompt = reprompt
-        });
+        };
     }
 
-    private static Cipher ComposeSshKey(
+    private static CipherSeed ComposeSshKey(
         int index,
         string encryptionKey,
         Guid? organizationId = null,
@@ -192,7 +193,7 @@ internal static class CipherComposer
         CipherRepromptType reprompt = CipherRepromptType.None)
     {
         var sshKey = SshKeyDataGenerator.GenerateByIndex(index);
-        return SshKeyCipherSeeder.Create(new CipherSeed
+        return new CipherSeed
         {
             Type = CipherType.SSHKey,
             Name = $"SSH Key {index + 1}",
@@ -201,7 +202,7 @@ internal static class CipherComposer
             UserId = userId,
             Reprompt = reprompt,
             SshKey = sshKey
-        });
+        };
     }
 
     /// <summary>
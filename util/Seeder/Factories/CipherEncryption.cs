@@ -40,6 +40,30 @@ internal static class CipherEncryption
             ?? throw new InvalidOperationException("Failed to parse encrypted cipher");
     }
 
+    /// <summary>
+    /// Encrypts many cipher views with one FFI call, in parallel on the Rust side. Results are in input order.
+    /// </summary>
+    internal static List<EncryptedCipherDto> EncryptMany(
+        IReadOnlyList<(CipherViewDto View, string KeyBase64, CipherEncryptionType Mode)> ciphers)
+    {
+        var batch = ciphers.Select(c => new BulkCipherDto(c.View, c.KeyBase64, (int)c.Mode));
+        var batchJson = JsonSerializer.Serialize(batch, _sdkJsonOptions);
+        var encryptedJson = RustSdkService.EncryptCiphersBulk(batchJson, _fieldPathsJson);
+        return JsonSerializer.Deserialize<List<EncryptedCipherDto>>(encryptedJson, _sdkJsonOptions)
+            ?? throw new InvalidOperationException("Failed to parse encrypted ciphers");
+    }
+
+    /// <summary>
+    /// Creates cipher entities for many seeds with one <see cref="EncryptMany"/> call. Entities are in input order.
+    /// </summary>
+    internal static List<Cipher> CreateMany(IReadOnlyList<CipherSeed> seeds)
+    {
+        var encrypted = EncryptMany(seeds.Select(s => (BuildView(s), s.EncryptionKey!, s.CipherEncryption)).ToList());
+        return seeds
+            .Select((s, i) => CreateEntity(encrypted[i], ToData(encrypted[i], s.Type), s.Type, s.OrganizationId, s.UserId))
+            .ToList();
+    }
+
     internal static Cipher CreateEntity(
         EncryptedCipherDto encrypted,
         object data,
@@ -62,4 +86,32 @@ internal static class CipherEncryption
             RevisionDate = DateTime.UtcNow
         };
     }
+
+    private static CipherViewDto BuildView(CipherSeed seed) => seed.Type switch
+    {
+        CipherType.Login => LoginCipherSeeder.BuildView(seed),
+        CipherType.Card => CardCipherSeeder.BuildView(seed),
+        CipherType.Identity => IdentityCipherSeeder.BuildView(seed),
+        CipherType.SecureNote => SecureNoteCipherSeeder.BuildView(seed),
+        CipherType.SSHKey => SshKeyCipherSeeder.BuildView(seed),
+        CipherType.BankAccount => BankAccountCipherSeeder.BuildView(seed),
+        CipherType.DriversLicense => DriversLicenseCipherSeeder.BuildView(seed),
+        CipherType.Passport => PassportCipherSeeder.BuildView(seed),
+        _ => throw new ArgumentOutOfRangeException(nameof(seed), $"Unsupported cipher type: {seed.Type}")
+    };
+
+    private static object ToData(EncryptedCipherDto encrypted, CipherType cipherType) => cipherType switch
+    {
+        CipherType.Login => encrypted.ToLoginData(),
+        CipherType.Card => encrypted.ToCardData(),
+        CipherType.Identity => encrypted.ToIdentityData(),
+        CipherType.SecureNote => encrypted.ToSecureNoteData(),
+        CipherType.SSHKey => encrypted.ToSshKeyData(),
+        CipherType.BankAccount => encrypted.ToBankAccountData(),
+        CipherType.DriversLicense => encrypted.ToDriversLicenseData(),
+        CipherType.Passport => encrypted.ToPassportData(),
+        _ => throw new ArgumentOutOfRangeException(nameof(cipherType), $"Unsupported cipher type: {cipherType}")
+    };
+
+    private sealed record BulkCipherDto(CipherViewDto View, string Key, int Mode);
 }
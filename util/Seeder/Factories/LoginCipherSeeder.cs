@@ -11,22 +11,21 @@ internal static class LoginCipherSeeder
 {
     internal static Cipher Create(CipherSeed options)
     {
-
-        var cipherView = new CipherViewDto
-        {
-            OrganizationId = options.OrganizationId,
-            Name = options.Name,
-            Notes = options.Notes,
-            Type = CipherTypes.Login,
-            Login = options.Login,
-            Fields = options.Fields,
-            Reprompt = (int)options.Reprompt
-        };
-
-        var encrypted = CipherEncryption.Encrypt(cipherView, options.EncryptionKey!, options.CipherEncryption);
+        var encrypted = CipherEncryption.Encrypt(BuildView(options), options.EncryptionKey!, options.CipherEncryption);
         return CipherEncryption.CreateEntity(encrypted, encrypted.ToLoginData(), CipherType.Login, options.OrganizationId, options.UserId);
     }
 
+    internal static CipherViewDto BuildView(CipherSeed options) => new()
+    {
+        OrganizationId = options.OrganizationId,
+        Name = options.Name,
+        Notes = options.Notes,
+        Type = CipherTypes.Login,
+        Login = options.Login,
+        Fields = options.Fields,
+        Reprompt = (int)options.Reprompt
+    };
+
     internal static Fido2CredentialViewDto CreateFido2Credential(string rpId, string rpName, string userName)
     {
         // Generate ECDSA P-256 private key in PKCS#8 format
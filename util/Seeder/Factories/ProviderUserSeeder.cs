@@ -16,7 +16,7 @@ internal static class ProviderUserSeeder
     /// (otherwise only the <see cref="ProviderUser.Email"/> invitation is recorded), and the encrypted provider
     /// key is only stored once the membership is <see cref="ProviderUserStatusType.Confirmed"/>.
     /// The caller computes <paramref name="encryptedProviderKey"/> via
-    /// <see cref="RustSdkService.GenerateUserOrganizationKey"/>.
+    /// <see cref="RustSdkService.GenerateProviderUserKey"/>.
     /// </summary>
     internal static ProviderUser CreateProviderUser(
         Provider provider,
@@ -51,6 +51,6 @@ internal static class ProviderUserSeeder
             owner,
             ProviderUserType.ProviderAdmin,
             ProviderUserStatusType.Confirmed,
-            RustSdkService.GenerateUserOrganizationKey(owner.PublicKey!, providerKey));
+            RustSdkService.GenerateProviderUserKey(owner.PublicKey!, providerKey));
     }
 }
@@ -0,0 +1,46 @@
+﻿using System.Security.Cryptography;
+using System.Text;
+using Bit.RustSDK;
+
+namespace Bit.Seeder.Factories;
+
+/// <summary>
+/// Picks RSA pool indexes so users, organizations and providers do not share key pairs: users cycle through the
+/// bottom of the pool, while the top <see cref="_reservedForOrganizations"/> entries are held back for
+/// organizations and providers. A pool too small to hold entries back is shared by everyone.
+/// </summary>
+internal static class RsaPoolIndex
+{
+    private const int _reservedForOrganizations = 10;
+
+    /// <summary>
+    /// The pool index of the <paramref name="index"/>-th seeded user, wrapping within the users' range.
+    /// </summary>
+    internal static uint ForUser(int index) => (uint)(index % UserRange(RustSdkService.RsaPoolSize()));
+
+    /// <summary>
+    /// Derives a deterministic pool index for an organization's key pair from its domain: re-seeding the same
+    /// domain reproduces the same key pair. The index falls among the entries held back from users.
+    /// </summary>
+    internal static uint ForOrganization(string domain) => Derive($"organization:{domain}");
+
+    /// <summary>
+    /// <see cref="ForOrganization"/> for a provider's key pair. An organization and a provider on the same domain
+    /// get unrelated indexes, though with only <see cref="_reservedForOrganizations"/> entries to share they may
+    /// still collide.
+    /// </summary>
+    internal static uint ForProvider(string domain) => Derive($"provider:{domain}");
+
+    private static uint Derive(string value)
+    {
+        var poolSize = RustSdkService.RsaPoolSize();
+        var userRange = UserRange(poolSize);
+        var reserved = userRange < poolSize ? poolSize - userRange : poolSize;
+
+        var hash = BitConverter.ToUInt32(SHA256.HashData(Encoding.UTF8.GetBytes(value)), 0);
+        return (uint)(poolSize - reserved) + hash % (uint)reserved;
+    }
+
+    private static int UserRange(int poolSize) =>
+        poolSize > _reservedForOrganizations ? poolSize - _reservedForOrganizations : poolSize;
+}
@@ -21,8 +21,9 @@ internal static class UserSeeder
         var keys = seed.Keys;
         var mangledEmail = keys == null ? manglerService.Mangle(seed.Email) : seed.Email;
 
-        keys ??= RustSdkService.GenerateUserKeys(
-            mangledEmail, seed.Password ?? DefaultPassword, seed.KdfIterations, seed.PoolIndex);
+        keys ??= RustSdkService.GenerateUserKeysWithKdf(
+            mangledEmail, seed.Password ?? DefaultPassword, (int)seed.Kdf, seed.KdfIterations,
+            seed.KdfMemory, seed.KdfParallelism, seed.PoolIndex);
 
         var user = new User
         {
@@ -46,8 +47,10 @@ internal static class UserSeeder
             ForcePasswordReset = seed.ForcePasswordReset,
             UsesKeyConnector = seed.UsesKeyConnector,
             ApiKey = CoreHelpers.SecureRandomString(30),
-            Kdf = KdfType.PBKDF2_SHA256,
-            KdfIterations = seed.KdfIterations
+            Kdf = (KdfType)keys.Kdf,
+            KdfIterations = keys.KdfIterations,
+            KdfMemory = keys.KdfMemory,
+            KdfParallelism = keys.KdfParallelism
         };
 
         // Set only when supplied so the entity's own default ("en-US") survives.
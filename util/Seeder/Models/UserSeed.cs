@@ -47,11 +47,26 @@ internal record UserSeed
     public string? Password { get; init; }
 
     /// <summary>
-    /// PBKDF2 only; Argon2 cannot be seeded. Defaults to 5,000 for speed — production default is
-    /// 600,000. Use that for realistic e2e runs.
+    /// Ignored when <see cref="Keys"/> is supplied; the user takes the KDF the keys were derived with.
+    /// </summary>
+    public KdfType Kdf { get; init; } = KdfType.PBKDF2_SHA256;
+
+    /// <summary>
+    /// Defaults to 5,000 for speed — the PBKDF2 production default is 600,000. Use that for realistic
+    /// e2e runs. Argon2id accounts need their own count (production default 3).
     /// </summary>
     public int KdfIterations { get; init; } = 5_000;
 
+    /// <summary>
+    /// Argon2id memory in MiB. Required for <see cref="KdfType.Argon2id"/>, ignored for PBKDF2.
+    /// </summary>
+    public int? KdfMemory { get; init; }
+
+    /// <summary>
+    /// Argon2id parallelism. Required for <see cref="KdfType.Argon2id"/>, ignored for PBKDF2.
+    /// </summary>
+    public int? KdfParallelism { get; init; }
+
     /// <summary>
     /// Spreads bulk callers across the SDK's pre-generated RSA keypair pool.
     /// Ignored when <see cref="Keys"/> is supplied.
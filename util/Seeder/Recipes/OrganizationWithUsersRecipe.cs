@@ -34,7 +34,7 @@ public class OrganizationWithUsersRecipe(
         var seats = Math.Max(users + 1, 1000);
 
         // Generate organization keys
-        var orgKeys = RustSdkService.GenerateOrganizationKeys();
+        var orgKeys = RustSdkService.GenerateOrganizationKeys(RsaPoolIndex.ForOrganization(domain));
         var organization = OrganizationSeeder.Create(
             new OrganizationSeed
             {
@@ -65,7 +65,7 @@ public class SingleOrganizationScene(
                 $"User {request.OwnerUserId} has no public key; cannot encrypt the organization key for the owner.");
         }
 
-        var orgKeys = RustSdkService.GenerateOrganizationKeys();
+        var orgKeys = RustSdkService.GenerateOrganizationKeys(RsaPoolIndex.ForOrganization(request.Domain));
 
         var organization = OrganizationSeeder.Create(
             new OrganizationSeed
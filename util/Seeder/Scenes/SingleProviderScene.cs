@@ -82,7 +82,7 @@ public class SingleProviderScene(
         var domain = string.IsNullOrWhiteSpace(request.Domain)
             ? $"{Guid.NewGuid():N}.provider.test"
             : request.Domain;
-        var providerKey = RustSdkService.GenerateOrganizationKeys().Key;
+        var providerKey = RustSdkService.GenerateProviderKeys(RsaPoolIndex.ForProvider(domain)).Key;
 
         var provider = ProviderSeeder.Create(
             new ProviderSeed
@@ -79,7 +79,7 @@ internal sealed class CreateOrganizationStep : IStep
         }
 
         var seats = _seats ?? PlanFeatures.GenerateRealisticSeatCount(_planType, domain);
-        var orgKeys = RustSdkService.GenerateOrganizationKeys();
+        var orgKeys = RustSdkService.GenerateOrganizationKeys(RsaPoolIndex.ForOrganization(domain));
         var organization = OrganizationSeeder.Create(
             new OrganizationSeed
             {
@@ -62,7 +62,7 @@ internal sealed class CreateRosterStep(string fixtureName) : IStep
 
             var mangledEmail = context.GetMangler().Mangle(email);
             var password = context.GetPassword();
-            var userKeys = RustSdkService.GenerateUserKeys(mangledEmail, password, kdfIterations, (uint)rosterIndex++);
+            var userKeys = RustSdkService.GenerateUserKeys(mangledEmail, password, kdfIterations, RsaPoolIndex.ForUser(rosterIndex++));
             var (user, _) = UserSeeder.Create(
                 new UserSeed
                 {
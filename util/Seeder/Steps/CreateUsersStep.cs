@@ -15,8 +15,6 @@ namespace Bit.Seeder.Steps;
 /// </summary>
 internal sealed class CreateUsersStep(int count, bool realisticStatusMix = false) : IStep
 {
-    private const int _rsaPoolSize = 100;
-
     public void Execute(SeederContext context)
     {
         var org = context.RequireOrganization();
@@ -42,6 +40,18 @@ internal sealed class CreateUsersStep(int count, bool realisticStatusMix = false
             statuses[i] = statusDistribution.Select(i, count);
         }
 
+        // One bulk call derives every master key in parallel on the Rust side.
+        var keys = RustSdkService.GenerateUserKeysBulk(
+            Enumerable.Range(0, count)
+                .Select(i => new UserKeysRequest
+                {
+                    Email = mangledEmails[i],
+                    Password = password,
+                    KdfIterations = kdfIterations,
+                    PoolIndex = RsaPoolIndex.ForUser(i)
+                })
+                .ToList());
+
         var results = new (User User, OrganizationUser OrgUser, UserKeys Keys, bool IsConfirmed)[count];
 
         progress?.Report(new PhaseStarted(SeederPhases.CreatingUsers, count));
@@ -54,7 +64,7 @@ internal sealed class CreateUsersStep(int count, bool realisticStatusMix = false
             localInit: () => 0,
             body: (i, _, localTicked) =>
             {
-                var userKeys = RustSdkService.GenerateUserKeys(mangledEmails[i], password, kdfIterations, (uint)(i % _rsaPoolSize));
+                var userKeys = keys[i];
                 var (user, _) = UserSeeder.Create(
                     new UserSeed
                     {
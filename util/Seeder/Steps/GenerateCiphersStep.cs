@@ -1,11 +1,11 @@
 ﻿using Bit.Core.Entities;
-using Bit.Core.Vault.Entities;
 using Bit.Core.Vault.Enums;
 using Bit.Seeder.Data;
 using Bit.Seeder.Data.Distributions;
 using Bit.Seeder.Data.Enums;
 using Bit.Seeder.Data.Static;
 using Bit.Seeder.Factories;
+using Bit.Seeder.Models;
 using Bit.Seeder.Options;
 using Bit.Seeder.Pipeline;
 
@@ -71,17 +71,23 @@ internal sealed class GenerateCiphersStep(
             ? ArchiveDeleteDistribution.AssignRoundRobinUserPositions(selection.ArchivedOrder, userDigests.Count)
             : new Dictionary<int, int>();
 
-        var ciphers = new List<Cipher>(count);
-        var cipherIds = new List<Guid>(count);
-        var collectionCiphers = new List<CollectionCipher>(count + count / 3);
-
+        var seeds = new List<CipherSeed>(count);
         for (var i = 0; i < count; i++)
         {
             var cipherType = typeDistribution.Select(i, count);
             var reprompt = repromptEveryNthCipher > 0 && i % repromptEveryNthCipher == 0
                 ? CipherRepromptType.Password
                 : CipherRepromptType.None;
-            var cipher = CipherComposer.Compose(i, cipherType, orgKey, companies, generator, passwordDistribution, organizationId: orgId, reprompt: reprompt);
+            seeds.Add(CipherComposer.Compose(i, cipherType, orgKey, companies, generator, passwordDistribution, organizationId: orgId, reprompt: reprompt));
+        }
+
+        var ciphers = CipherEncryption.CreateMany(seeds);
+        var cipherIds = new List<Guid>(count);
+        var collectionCiphers = new List<CollectionCipher>(count + count / 3);
+
+        for (var i = 0; i < count; i++)
+        {
+            var cipher = ciphers[i];
 
             if (userDigests is { Count: > 0 } && userFolderIds is not null)
             {
@@ -91,7 +97,6 @@ internal sealed class GenerateCiphersStep(
 
             CipherComposer.AssignArchiveOrDeleteState(cipher, i, selection, idx => userDigests[archivedUserPositions[idx]].UserId);
 
-            ciphers.Add(cipher);
             cipherIds.Add(cipher.Id);
         }
 
@@ -4,6 +4,7 @@ using Bit.Seeder.Data.Distributions;
 using Bit.Seeder.Data.Enums;
 using Bit.Seeder.Data.Static;
 using Bit.Seeder.Factories;
+using Bit.Seeder.Models;
 using Bit.Seeder.Options;
 using Bit.Seeder.Pipeline;
 
@@ -95,7 +96,7 @@ internal sealed class GeneratePersonalCiphersStep(
         // per-user loop.
         var selection = ArchiveDeleteDistribution.Select(expectedTotal, archivedTarget, bothTarget, deletedOnlyTarget);
 
-        var userCiphers = new Cipher[userDigests.Count][];
+        var userCiphers = new List<Cipher>[userDigests.Count];
 
         progress?.Report(new PhaseStarted(SeederPhases.CreatingPersonalCiphers, expectedTotal));
         var batchSize = Math.Max(1, expectedTotal / 100);
@@ -110,7 +111,7 @@ internal sealed class GeneratePersonalCiphersStep(
                 var userDigest = userDigests[u];
                 var localCount = userCounts[u];
                 var baseOffset = offsets[u];
-                var localCiphers = new Cipher[localCount];
+                var seeds = new CipherSeed[localCount];
 
                 for (var i = 0; i < localCount; i++)
                 {
@@ -119,13 +120,16 @@ internal sealed class GeneratePersonalCiphersStep(
                     var reprompt = repromptEveryNthCipher > 0 && globalIndex % repromptEveryNthCipher == 0
                         ? CipherRepromptType.Password
                         : CipherRepromptType.None;
-                    var cipher = CipherComposer.Compose(globalIndex, cipherType, userDigest.SymmetricKey, companies, generator, passwordDistribution, userId: userDigest.UserId, reprompt: reprompt);
-
-                    CipherComposer.AssignFolder(cipher, userDigest.UserId, i, userFolderIds);
+                    seeds[i] = CipherComposer.Compose(globalIndex, cipherType, userDigest.SymmetricKey, companies, generator, passwordDistribution, userId: userDigest.UserId, reprompt: reprompt);
+                }
 
-                    CipherComposer.AssignArchiveOrDeleteState(cipher, globalIndex, selection, _ => userDigest.UserId);
+                // One FFI call per user; the Rust side encrypts the user's ciphers in parallel.
+                var localCiphers = CipherEncryption.CreateMany(seeds);
+                for (var i = 0; i < localCount; i++)
+                {
+                    CipherComposer.AssignFolder(localCiphers[i], userDigest.UserId, i, userFolderIds);
 
-                    localCiphers[i] = cipher;
+                    CipherComposer.AssignArchiveOrDeleteState(localCiphers[i], baseOffset + i, selection, _ => userDigest.UserId);
                 }
 
                 userCiphers[u] = localCiphers;